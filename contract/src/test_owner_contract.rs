@@ -0,0 +1,57 @@
+//! Contract standing in for a multisig or DAO owning the proxy. `forward` calls an entry point of
+//! the proxy, which then sees the package hash of this contract as its caller.
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    bytesrepr::{self, Bytes},
+    contracts::NamedKeys,
+    CLType, CLTyped, ContractPackageHash, EntryPoint, EntryPointAccess, EntryPointType,
+    EntryPoints, Parameter, RuntimeArgs,
+};
+
+#[no_mangle]
+pub extern "C" fn forward() {
+    let proxy_package_hash: ContractPackageHash = runtime::get_named_arg("proxy_package_hash");
+    let entry_point: String = runtime::get_named_arg("entry_point");
+    let args: Bytes = runtime::get_named_arg("args");
+    let args: RuntimeArgs = bytesrepr::deserialize(args.to_vec()).unwrap_or_revert();
+    runtime::call_versioned_contract::<()>(proxy_package_hash, None, &entry_point, args)
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(EntryPoint::new(
+        "forward",
+        vec![
+            Parameter::new("proxy_package_hash", ContractPackageHash::cl_type()),
+            Parameter::new("entry_point", String::cl_type()),
+            Parameter::new("args", Bytes::cl_type()),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    let (contract_package_hash, access_uref) = storage::create_contract_package_at_hash();
+    let (contract_hash, _) =
+        storage::add_contract_version(contract_package_hash, entry_points, NamedKeys::new());
+    runtime::put_key("proxy_owner_access_token", access_uref.into());
+    runtime::put_key(
+        "proxy_owner_package_hash_wrapped",
+        storage::new_uref(contract_package_hash).into(),
+    );
+    runtime::put_key(
+        "proxy_owner_contract_hash_wrapped",
+        storage::new_uref(contract_hash).into(),
+    );
+}
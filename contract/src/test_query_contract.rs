@@ -0,0 +1,61 @@
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use casper_contract::{
+    contract_api::{runtime, storage},
+    ext_ffi,
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    api_error,
+    bytesrepr::{Bytes, ToBytes},
+    ContractPackageHash,
+};
+
+/// Calls a read-only entry point of the proxy and stores the serialized return value under the
+/// `query_result` named key of the calling account, where the tests read it back.
+#[no_mangle]
+pub extern "C" fn call() {
+    let proxy_package_hash: ContractPackageHash = runtime::get_named_arg("proxy_package_hash");
+    let entry_point: String = runtime::get_named_arg("entry_point");
+    // The args are passed on serialized, so the query works for any entry point.
+    let args: Bytes = runtime::get_named_arg("args");
+    let package_bytes = proxy_package_hash.value();
+    let version_bytes = Option::<u32>::None.to_bytes().unwrap_or_revert();
+    let entry_point_bytes = entry_point.to_bytes().unwrap_or_revert();
+    let mut result_size: usize = 0;
+    let ret = unsafe {
+        ext_ffi::casper_call_versioned_contract(
+            package_bytes.as_ptr(),
+            package_bytes.len(),
+            version_bytes.as_ptr(),
+            version_bytes.len(),
+            entry_point_bytes.as_ptr(),
+            entry_point_bytes.len(),
+            args.as_ptr(),
+            args.len(),
+            &mut result_size as *mut usize,
+        )
+    };
+    api_error::result_from(ret).unwrap_or_revert();
+    let mut result = vec![0u8; result_size];
+    if result_size > 0 {
+        let mut bytes_written: usize = 0;
+        let ret = unsafe {
+            ext_ffi::casper_read_host_buffer(
+                result.as_mut_ptr(),
+                result_size,
+                &mut bytes_written as *mut usize,
+            )
+        };
+        api_error::result_from(ret).unwrap_or_revert();
+    }
+    runtime::put_key(
+        "query_result",
+        storage::new_uref(Bytes::from(result)).into(),
+    );
+}
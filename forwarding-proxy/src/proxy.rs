@@ -0,0 +1,280 @@
+//! Proxy forwarding boolean checks whose signatures are chosen on install. The installing deploy
+//! passes the checks as `checks`, a serialized `Vec<(String, Vec<Parameter>)>`, and every call to
+//! one of them is forwarded to the providers with the declared args, once they are checked to be
+//! well-formed values of the declared types.
+//!
+//! A wasm module can only be called through the functions it exports, so the names of the checks
+//! are limited to the ones listed in `forwarded_checks!` below, their parameters are free except
+//! for `URef` and `Any` parameters.
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use casper_contract::{
+    contract_api::{
+        runtime::{self, revert},
+        storage,
+    },
+    ext_ffi,
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    account::AccountHash,
+    api_error,
+    bytesrepr::{self, Bytes, FromBytes, ToBytes},
+    contracts::NamedKeys,
+    ApiError, CLType, CLValue, Key, Parameter, PublicKey, RuntimeArgs, U128, U256, U512,
+};
+use proxy_core::{Probe, ProxyConfig};
+
+const CONFIG: ProxyConfig = ProxyConfig {
+    index_dictionary: "forwarding_provider_index",
+    active_dictionary: "forwarding_active_providers",
+    records_dictionary: "forwarding_provider_records",
+    baseline_dictionary: None,
+    roles_dictionary: "forwarding_roles",
+    overrides_dictionary: "forwarding_account_overrides",
+    cache_dictionary: None,
+    named_keys_infix: "forwarding",
+    add_provider: "add_provider",
+    remove_provider: "remove_provider",
+    ban_provider: "ban_provider",
+    ban_provider_until: "ban_provider_until",
+    unban_provider: "unban_provider",
+    checks: &[],
+    probes: Some(probes),
+};
+
+/// Exports every check as `$check` and `$detailed`, calling the provider entry point `$check`.
+macro_rules! forwarded_checks {
+    ($(($check:ident, $detailed:ident)),* $(,)?) => {
+        $(
+            #[no_mangle]
+            pub extern "C" fn $check() {
+                let name = stringify!($check);
+                proxy_core::forward(&CONFIG, name, forwarded_args(name))
+            }
+
+            #[no_mangle]
+            pub extern "C" fn $detailed() {
+                let name = stringify!($check);
+                proxy_core::forward_detailed(&CONFIG, name, forwarded_args(name))
+            }
+        )*
+
+        /// Names of the exported checks and their detailed variants.
+        const FORWARDABLE: &[(&str, &str)] = &[$((stringify!($check), stringify!($detailed))),*];
+    };
+}
+
+forwarded_checks! {
+    (is_kyc_proved, is_kyc_proved_detailed),
+    (is_enabled, is_enabled_detailed),
+    (is_allowed, is_allowed_detailed),
+    (is_accredited, is_accredited_detailed),
+    (is_sanctions_cleared, is_sanctions_cleared_detailed),
+}
+
+fn stored_checks() -> Vec<(String, Vec<Parameter>)> {
+    let checks_uref = runtime::get_key("checks")
+        .unwrap_or_revert()
+        .into_uref()
+        .unwrap_or_revert();
+    let checks: Bytes = storage::read(checks_uref)
+        .unwrap_or_revert()
+        .unwrap_or_revert();
+    bytesrepr::deserialize(checks.to_vec()).unwrap_or_revert()
+}
+
+/// Copies the declared args of `check` from the current call, args the caller left out are left
+/// out for the providers as well. Only the serialized value of an arg can be read, so it is parsed
+/// as the declared type and the call reverts if it is not a well-formed value of that type.
+fn forwarded_args(check: &str) -> RuntimeArgs {
+    let (_, parameters) = stored_checks()
+        .into_iter()
+        .find(|(name, _)| name == check)
+        .unwrap_or_revert();
+    let mut args = RuntimeArgs::new();
+    for parameter in parameters {
+        let (name, cl_type) = parameter.into_parts();
+        if let Some(bytes) = get_raw_named_arg(&name) {
+            if !parse(&cl_type, &bytes).unwrap_or_revert().is_empty() {
+                revert(ApiError::LeftOverBytes)
+            }
+            args.insert_cl_value(name, CLValue::from_components(cl_type, bytes));
+        }
+    }
+    args
+}
+
+/// Checks that `bytes` start with a serialized value of `cl_type` and returns the bytes after it.
+fn parse<'a>(cl_type: &CLType, bytes: &'a [u8]) -> Result<&'a [u8], bytesrepr::Error> {
+    fn skip<T: FromBytes>(bytes: &[u8]) -> Result<&[u8], bytesrepr::Error> {
+        T::from_bytes(bytes).map(|(_, remainder)| remainder)
+    }
+
+    match cl_type {
+        CLType::Bool => skip::<bool>(bytes),
+        CLType::I32 => skip::<i32>(bytes),
+        CLType::I64 => skip::<i64>(bytes),
+        CLType::U8 => skip::<u8>(bytes),
+        CLType::U32 => skip::<u32>(bytes),
+        CLType::U64 => skip::<u64>(bytes),
+        CLType::U128 => skip::<U128>(bytes),
+        CLType::U256 => skip::<U256>(bytes),
+        CLType::U512 => skip::<U512>(bytes),
+        CLType::Unit => Ok(bytes),
+        CLType::String => skip::<String>(bytes),
+        CLType::Key => skip::<Key>(bytes),
+        CLType::PublicKey => skip::<PublicKey>(bytes),
+        CLType::Option(inner) => match u8::from_bytes(bytes)? {
+            (0, remainder) => Ok(remainder),
+            (1, remainder) => parse(inner, remainder),
+            _ => Err(bytesrepr::Error::Formatting),
+        },
+        CLType::List(inner) => {
+            let (len, mut remainder) = u32::from_bytes(bytes)?;
+            for _ in 0..len {
+                remainder = parse(inner, remainder)?;
+            }
+            Ok(remainder)
+        }
+        CLType::ByteArray(len) => {
+            bytesrepr::safe_split_at(bytes, *len as usize).map(|(_, remainder)| remainder)
+        }
+        CLType::Result { ok, err } => match u8::from_bytes(bytes)? {
+            (0, remainder) => parse(err, remainder),
+            (1, remainder) => parse(ok, remainder),
+            _ => Err(bytesrepr::Error::Formatting),
+        },
+        CLType::Map { key, value } => {
+            let (len, mut remainder) = u32::from_bytes(bytes)?;
+            for _ in 0..len {
+                remainder = parse(value, parse(key, remainder)?)?;
+            }
+            Ok(remainder)
+        }
+        CLType::Tuple1(types) => parse_all(types, bytes),
+        CLType::Tuple2(types) => parse_all(types, bytes),
+        CLType::Tuple3(types) => parse_all(types, bytes),
+        // rejected on install, see `placeholder`
+        CLType::URef | CLType::Any => Err(bytesrepr::Error::Formatting),
+    }
+}
+
+fn parse_all<'a>(types: &[Box<CLType>], mut bytes: &'a [u8]) -> Result<&'a [u8], bytesrepr::Error> {
+    for cl_type in types {
+        bytes = parse(cl_type, bytes)?;
+    }
+    Ok(bytes)
+}
+
+/// Probes for the forwarded checks, every declared arg is set to a placeholder value.
+fn probes() -> Vec<Probe> {
+    stored_checks()
+        .into_iter()
+        .map(|(name, parameters)| (name, probe_args(&parameters)))
+        .collect()
+}
+
+fn probe_args(parameters: &[Parameter]) -> RuntimeArgs {
+    let mut args = RuntimeArgs::new();
+    for parameter in parameters {
+        let mut bytes = Vec::new();
+        placeholder(parameter.cl_type(), &mut bytes);
+        args.insert_cl_value(
+            parameter.name(),
+            CLValue::from_components(parameter.cl_type().clone(), bytes),
+        );
+    }
+    args
+}
+
+/// Appends a placeholder value of `cl_type` to `bytes`, zero, empty or `None` where possible.
+/// Forwarding a `URef` would need access to it and `Any` has no known layout, checks with such
+/// parameters revert the install with `ApiError::User(315)`.
+fn placeholder(cl_type: &CLType, bytes: &mut Vec<u8>) {
+    match cl_type {
+        CLType::Unit => {}
+        CLType::Bool | CLType::U8 | CLType::Option(_) => bytes.push(0),
+        // big integers are prefixed with the number of their bytes, which is zero for zero
+        CLType::U128 | CLType::U256 | CLType::U512 => bytes.push(0),
+        // strings, lists and maps are prefixed with their length
+        CLType::I32 | CLType::U32 | CLType::String | CLType::List(_) | CLType::Map { .. } => {
+            bytes.extend_from_slice(&[0; 4])
+        }
+        CLType::I64 | CLType::U64 => bytes.extend_from_slice(&[0; 8]),
+        CLType::Key => bytes.append(
+            &mut Key::Account(AccountHash::default())
+                .to_bytes()
+                .unwrap_or_revert(),
+        ),
+        CLType::PublicKey => bytes.append(&mut PublicKey::System.to_bytes().unwrap_or_revert()),
+        CLType::ByteArray(len) => bytes.resize(bytes.len() + *len as usize, 0),
+        CLType::Result { ok, .. } => {
+            bytes.push(1);
+            placeholder(ok, bytes)
+        }
+        CLType::Tuple1(types) => types.iter().for_each(|cl_type| placeholder(cl_type, bytes)),
+        CLType::Tuple2(types) => types.iter().for_each(|cl_type| placeholder(cl_type, bytes)),
+        CLType::Tuple3(types) => types.iter().for_each(|cl_type| placeholder(cl_type, bytes)),
+        CLType::URef | CLType::Any => revert(ApiError::User(315)),
+    }
+}
+
+/// Reads the serialized value of a named arg without deserializing it.
+fn get_raw_named_arg(name: &str) -> Option<Vec<u8>> {
+    let mut arg_size: usize = 0;
+    let ret = unsafe {
+        ext_ffi::casper_get_named_arg_size(name.as_ptr(), name.len(), &mut arg_size as *mut usize)
+    };
+    match api_error::result_from(ret) {
+        Ok(()) => {}
+        Err(ApiError::MissingArgument) => return None,
+        Err(error) => revert(error),
+    }
+    let mut bytes = vec![0u8; arg_size];
+    if arg_size > 0 {
+        let ret = unsafe {
+            ext_ffi::casper_get_named_arg(name.as_ptr(), name.len(), bytes.as_mut_ptr(), arg_size)
+        };
+        api_error::result_from(ret).unwrap_or_revert();
+    }
+    Some(bytes)
+}
+
+proxy_core::export_entry_points! {
+    config: CONFIG,
+    add_provider: add_provider,
+    remove_provider: remove_provider,
+    ban_provider: ban_provider,
+    ban_provider_until: ban_provider_until,
+    unban_provider: unban_provider,
+    checks: [],
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let checks: Bytes = runtime::get_named_arg("checks");
+    let mut entry_points = proxy_core::entry_points(&CONFIG);
+    let forwarded: Vec<(String, Vec<Parameter>)> =
+        bytesrepr::deserialize(checks.to_vec()).unwrap_or_revert();
+    for (name, parameters) in forwarded {
+        let (_, detailed) = FORWARDABLE
+            .iter()
+            .find(|(check, _)| *check == name)
+            .unwrap_or_revert_with(ApiError::User(309));
+        // fails the install for parameter types that cannot be probed or forwarded
+        probe_args(&parameters);
+        proxy_core::add_check_entry_points(&mut entry_points, &name, Some(*detailed), parameters);
+    }
+
+    let mut named_keys = NamedKeys::new();
+    named_keys.insert("checks".into(), storage::new_uref(checks).into());
+    proxy_core::install_with(&CONFIG, entry_points, named_keys)
+}
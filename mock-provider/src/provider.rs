@@ -0,0 +1,211 @@
+//! Provider used by the test suite. It answers `is_kyc_proved`, `is_enabled` and `is_allowed` with
+//! the per-account answers set through `set_answer`, accounts without an answer are refused.
+//! `is_allowed` can additionally be limited to amounts up to `set_max_amount`, and `set_revert`
+//! makes every check revert, e.g. to simulate a broken provider.
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use casper_contract::{
+    contract_api::{
+        runtime::{self, revert},
+        storage::{self, dictionary_get, dictionary_put, new_dictionary},
+    },
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    contracts::NamedKeys, ApiError, CLType, CLTyped, CLValue, ContractPackageHash, EntryPoint,
+    EntryPointAccess, EntryPointType, EntryPoints, Key, Parameter, RuntimeArgs, URef, U256, U512,
+};
+
+const CHECKS: &[&str] = &["is_kyc_proved", "is_enabled", "is_allowed"];
+const MAX_AMOUNTS_DICTIONARY: &str = "max_amounts";
+const REVERT_KEY: &str = "revert";
+
+#[no_mangle]
+pub extern "C" fn init() {
+    if runtime::has_key(REVERT_KEY) {
+        revert(ApiError::User(402))
+    }
+    for check in CHECKS {
+        new_dictionary(&answers_dictionary(check)).unwrap_or_revert();
+    }
+    new_dictionary(MAX_AMOUNTS_DICTIONARY).unwrap_or_revert();
+    runtime::put_key(REVERT_KEY, storage::new_uref(false).into());
+}
+
+#[no_mangle]
+pub extern "C" fn is_kyc_proved() {
+    ret(answer("is_kyc_proved"))
+}
+
+#[no_mangle]
+pub extern "C" fn is_enabled() {
+    ret(answer("is_enabled"))
+}
+
+#[no_mangle]
+pub extern "C" fn is_allowed() {
+    let amount: U512 = runtime::get_named_arg("amount");
+    let max_amount: Option<U512> = dictionary_get(
+        named_uref(MAX_AMOUNTS_DICTIONARY),
+        &item_key(runtime::get_named_arg("account")),
+    )
+    .unwrap_or_revert()
+    .flatten();
+    ret(answer("is_allowed") && max_amount.map_or(true, |max_amount| amount <= max_amount))
+}
+
+#[no_mangle]
+pub extern "C" fn set_answer() {
+    let check: String = runtime::get_named_arg("check");
+    if !CHECKS.contains(&check.as_str()) {
+        revert(ApiError::User(401))
+    }
+    let account: Key = runtime::get_named_arg("account");
+    let answer: bool = runtime::get_named_arg("answer");
+    dictionary_put(
+        named_uref(&answers_dictionary(&check)),
+        &item_key(account),
+        answer,
+    );
+}
+
+#[no_mangle]
+pub extern "C" fn set_max_amount() {
+    let account: Key = runtime::get_named_arg("account");
+    let max_amount: Option<U512> = runtime::get_named_arg("max_amount");
+    dictionary_put(
+        named_uref(MAX_AMOUNTS_DICTIONARY),
+        &item_key(account),
+        max_amount,
+    );
+}
+
+#[no_mangle]
+pub extern "C" fn set_revert() {
+    storage::write(
+        named_uref(REVERT_KEY),
+        runtime::get_named_arg::<bool>("revert"),
+    );
+}
+
+/// Returns the answer set for the `account` arg, reverting with `ApiError::User(400)` while the
+/// provider is told to revert.
+fn answer(check: &str) -> bool {
+    let reverts: bool = storage::read(named_uref(REVERT_KEY))
+        .unwrap_or_revert()
+        .unwrap_or_revert();
+    if reverts {
+        revert(ApiError::User(400))
+    }
+    dictionary_get(
+        named_uref(&answers_dictionary(check)),
+        &item_key(runtime::get_named_arg("account")),
+    )
+    .unwrap_or_revert()
+    .unwrap_or_default()
+}
+
+fn ret(answer: bool) {
+    runtime::ret(CLValue::from_t(answer).unwrap_or_revert())
+}
+
+fn answers_dictionary(check: &str) -> String {
+    format!("{}_answers", check)
+}
+
+fn named_uref(name: &str) -> URef {
+    *runtime::get_key(name)
+        .unwrap_or_revert()
+        .as_uref()
+        .unwrap_or_revert()
+}
+
+/// Hex encoded hash of the account, the same item key the proxies use for accounts.
+fn item_key(account: Key) -> String {
+    match account {
+        Key::Account(account_hash) => ContractPackageHash::new(account_hash.value()).to_string(),
+        Key::Hash(hash) => ContractPackageHash::new(hash).to_string(),
+        _ => revert(ApiError::User(403)),
+    }
+}
+
+fn check_parameters() -> Vec<Parameter> {
+    vec![
+        Parameter::new("account", Key::cl_type()),
+        Parameter::new("index", CLType::Option(Box::new(U256::cl_type()))),
+    ]
+}
+
+fn entry_point(name: &str, parameters: Vec<Parameter>, ret: CLType) -> EntryPoint {
+    EntryPoint::new(
+        name,
+        parameters,
+        ret,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let name: String = runtime::get_named_arg("name");
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(entry_point("init", vec![], CLType::Unit));
+    entry_points.add_entry_point(entry_point(
+        "is_kyc_proved",
+        check_parameters(),
+        CLType::Bool,
+    ));
+    entry_points.add_entry_point(entry_point("is_enabled", check_parameters(), CLType::Bool));
+    let mut is_allowed_parameters = check_parameters();
+    is_allowed_parameters.push(Parameter::new("amount", CLType::U512));
+    entry_points.add_entry_point(entry_point(
+        "is_allowed",
+        is_allowed_parameters,
+        CLType::Bool,
+    ));
+    entry_points.add_entry_point(entry_point(
+        "set_answer",
+        vec![
+            Parameter::new("check", String::cl_type()),
+            Parameter::new("account", Key::cl_type()),
+            Parameter::new("answer", bool::cl_type()),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(entry_point(
+        "set_max_amount",
+        vec![
+            Parameter::new("account", Key::cl_type()),
+            Parameter::new("max_amount", CLType::Option(Box::new(U512::cl_type()))),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(entry_point(
+        "set_revert",
+        vec![Parameter::new("revert", bool::cl_type())],
+        CLType::Unit,
+    ));
+
+    let (contract_package_hash, access_uref) = storage::create_contract_package_at_hash();
+    let (contract_hash, _) =
+        storage::add_contract_version(contract_package_hash, entry_points, NamedKeys::new());
+    runtime::put_key(&format!("{}_access_token", name), access_uref.into());
+    runtime::put_key(
+        &format!("{}_package_hash_wrapped", name),
+        storage::new_uref(contract_package_hash).into(),
+    );
+    runtime::put_key(
+        &format!("{}_contract_hash_wrapped", name),
+        storage::new_uref(contract_hash).into(),
+    );
+    runtime::call_versioned_contract::<()>(contract_package_hash, None, "init", RuntimeArgs::new());
+}
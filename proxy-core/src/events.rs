@@ -0,0 +1,330 @@
+//! Registry events following the Casper Event Standard. Events are serialized into the `__events`
+//! dictionary under their index, `__events_length` holds the number of emitted events and
+//! `__events_schema` describes the fields of every event so indexers can decode them.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::bytesrepr::{self, Bytes, ToBytes};
+use casper_types::{CLType, CLTyped, ContractPackageHash, ContractVersion, Key, URef};
+
+const EVENTS_DICT: &str = "__events";
+const EVENTS_LENGTH: &str = "__events_length";
+const EVENTS_SCHEMA: &str = "__events_schema";
+const CES_VERSION_KEY: &str = "__events_ces_version";
+const CES_VERSION: &str = "0.1.0";
+
+pub enum Event {
+    ProviderAdded {
+        provider: ContractPackageHash,
+    },
+    ProviderRemoved {
+        provider: ContractPackageHash,
+    },
+    ProviderBanned {
+        provider: ContractPackageHash,
+    },
+    ProviderBannedUntil {
+        provider: ContractPackageHash,
+        until: u64,
+    },
+    ProviderUnbanned {
+        provider: ContractPackageHash,
+    },
+    ProviderMoved {
+        provider: ContractPackageHash,
+        new_index: u64,
+    },
+    ProviderWeightSet {
+        provider: ContractPackageHash,
+        weight: u32,
+    },
+    ProviderVersionSet {
+        provider: ContractPackageHash,
+        version: Option<ContractVersion>,
+    },
+    CacheTtlSet {
+        ttl: Option<u64>,
+    },
+    CacheEntryInvalidated {
+        entry: String,
+    },
+    CacheFlushed {
+        epoch: u64,
+    },
+    AggregationPolicySet {
+        policy: u8,
+        quorum: u32,
+    },
+    RoleGranted {
+        role: u8,
+        account: Key,
+    },
+    RoleRevoked {
+        role: u8,
+        account: Key,
+    },
+    Paused {
+        revert_checks: bool,
+    },
+    Unpaused,
+    AccountDenied {
+        account: Key,
+    },
+    AccountAllowed {
+        account: Key,
+    },
+    AccountOverrideRemoved {
+        account: Key,
+    },
+    OwnershipTransferStarted {
+        admin: Key,
+        pending_admin: Key,
+    },
+    OwnershipTransferCancelled {
+        admin: Key,
+    },
+    OwnershipTransferred {
+        previous_admin: Key,
+        new_admin: Key,
+    },
+}
+
+impl Event {
+    fn name(&self) -> &'static str {
+        match self {
+            Event::ProviderAdded { .. } => "ProviderAdded",
+            Event::ProviderRemoved { .. } => "ProviderRemoved",
+            Event::ProviderBanned { .. } => "ProviderBanned",
+            Event::ProviderBannedUntil { .. } => "ProviderBannedUntil",
+            Event::ProviderUnbanned { .. } => "ProviderUnbanned",
+            Event::ProviderMoved { .. } => "ProviderMoved",
+            Event::ProviderWeightSet { .. } => "ProviderWeightSet",
+            Event::ProviderVersionSet { .. } => "ProviderVersionSet",
+            Event::CacheTtlSet { .. } => "CacheTtlSet",
+            Event::CacheEntryInvalidated { .. } => "CacheEntryInvalidated",
+            Event::CacheFlushed { .. } => "CacheFlushed",
+            Event::AggregationPolicySet { .. } => "AggregationPolicySet",
+            Event::RoleGranted { .. } => "RoleGranted",
+            Event::RoleRevoked { .. } => "RoleRevoked",
+            Event::Paused { .. } => "Paused",
+            Event::Unpaused => "Unpaused",
+            Event::AccountDenied { .. } => "AccountDenied",
+            Event::AccountAllowed { .. } => "AccountAllowed",
+            Event::AccountOverrideRemoved { .. } => "AccountOverrideRemoved",
+            Event::OwnershipTransferStarted { .. } => "OwnershipTransferStarted",
+            Event::OwnershipTransferCancelled { .. } => "OwnershipTransferCancelled",
+            Event::OwnershipTransferred { .. } => "OwnershipTransferred",
+        }
+    }
+
+    /// The event name prefixed with `event_`, followed by the fields in schema order.
+    fn serialize(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut bytes = format!("event_{}", self.name()).to_bytes()?;
+        match self {
+            Event::ProviderAdded { provider }
+            | Event::ProviderRemoved { provider }
+            | Event::ProviderBanned { provider }
+            | Event::ProviderUnbanned { provider } => bytes.append(&mut provider.to_bytes()?),
+            Event::ProviderBannedUntil { provider, until } => {
+                bytes.append(&mut provider.to_bytes()?);
+                bytes.append(&mut until.to_bytes()?);
+            }
+            Event::ProviderMoved {
+                provider,
+                new_index,
+            } => {
+                bytes.append(&mut provider.to_bytes()?);
+                bytes.append(&mut new_index.to_bytes()?);
+            }
+            Event::ProviderWeightSet { provider, weight } => {
+                bytes.append(&mut provider.to_bytes()?);
+                bytes.append(&mut weight.to_bytes()?);
+            }
+            Event::ProviderVersionSet { provider, version } => {
+                bytes.append(&mut provider.to_bytes()?);
+                bytes.append(&mut version.to_bytes()?);
+            }
+            Event::CacheTtlSet { ttl } => bytes.append(&mut ttl.to_bytes()?),
+            Event::CacheEntryInvalidated { entry } => bytes.append(&mut entry.to_bytes()?),
+            Event::CacheFlushed { epoch } => bytes.append(&mut epoch.to_bytes()?),
+            Event::AggregationPolicySet { policy, quorum } => {
+                bytes.append(&mut policy.to_bytes()?);
+                bytes.append(&mut quorum.to_bytes()?);
+            }
+            Event::RoleGranted { role, account } | Event::RoleRevoked { role, account } => {
+                bytes.append(&mut role.to_bytes()?);
+                bytes.append(&mut account.to_bytes()?);
+            }
+            Event::Paused { revert_checks } => bytes.append(&mut revert_checks.to_bytes()?),
+            Event::Unpaused => {}
+            Event::AccountDenied { account }
+            | Event::AccountAllowed { account }
+            | Event::AccountOverrideRemoved { account } => bytes.append(&mut account.to_bytes()?),
+            Event::OwnershipTransferStarted {
+                admin,
+                pending_admin,
+            } => {
+                bytes.append(&mut admin.to_bytes()?);
+                bytes.append(&mut pending_admin.to_bytes()?);
+            }
+            Event::OwnershipTransferCancelled { admin } => bytes.append(&mut admin.to_bytes()?),
+            Event::OwnershipTransferred {
+                previous_admin,
+                new_admin,
+            } => {
+                bytes.append(&mut previous_admin.to_bytes()?);
+                bytes.append(&mut new_admin.to_bytes()?);
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+/// Field names and types of every event, keyed by event name.
+struct Schemas(BTreeMap<String, Vec<(String, CLType)>>);
+
+impl Schemas {
+    fn new() -> Self {
+        let provider = || (String::from("provider"), ContractPackageHash::cl_type());
+        let account = || (String::from("account"), Key::cl_type());
+        let role = || {
+            vec![
+                (String::from("role"), CLType::U8),
+                (String::from("account"), Key::cl_type()),
+            ]
+        };
+        let mut schemas = BTreeMap::new();
+        schemas.insert(String::from("ProviderAdded"), vec![provider()]);
+        schemas.insert(String::from("ProviderRemoved"), vec![provider()]);
+        schemas.insert(String::from("ProviderBanned"), vec![provider()]);
+        schemas.insert(
+            String::from("ProviderBannedUntil"),
+            vec![provider(), (String::from("until"), CLType::U64)],
+        );
+        schemas.insert(String::from("ProviderUnbanned"), vec![provider()]);
+        schemas.insert(
+            String::from("ProviderMoved"),
+            vec![provider(), (String::from("new_index"), CLType::U64)],
+        );
+        schemas.insert(
+            String::from("ProviderWeightSet"),
+            vec![provider(), (String::from("weight"), CLType::U32)],
+        );
+        schemas.insert(
+            String::from("ProviderVersionSet"),
+            vec![
+                provider(),
+                (
+                    String::from("version"),
+                    Option::<ContractVersion>::cl_type(),
+                ),
+            ],
+        );
+        schemas.insert(
+            String::from("CacheTtlSet"),
+            vec![(String::from("ttl"), Option::<u64>::cl_type())],
+        );
+        schemas.insert(
+            String::from("CacheEntryInvalidated"),
+            vec![(String::from("entry"), CLType::String)],
+        );
+        schemas.insert(
+            String::from("CacheFlushed"),
+            vec![(String::from("epoch"), CLType::U64)],
+        );
+        schemas.insert(
+            String::from("AggregationPolicySet"),
+            vec![
+                (String::from("policy"), CLType::U8),
+                (String::from("quorum"), CLType::U32),
+            ],
+        );
+        schemas.insert(String::from("RoleGranted"), role());
+        schemas.insert(String::from("RoleRevoked"), role());
+        schemas.insert(
+            String::from("Paused"),
+            vec![(String::from("revert_checks"), CLType::Bool)],
+        );
+        schemas.insert(String::from("Unpaused"), vec![]);
+        schemas.insert(String::from("AccountDenied"), vec![account()]);
+        schemas.insert(String::from("AccountAllowed"), vec![account()]);
+        schemas.insert(String::from("AccountOverrideRemoved"), vec![account()]);
+        schemas.insert(
+            String::from("OwnershipTransferStarted"),
+            vec![
+                (String::from("admin"), Key::cl_type()),
+                (String::from("pending_admin"), Key::cl_type()),
+            ],
+        );
+        schemas.insert(
+            String::from("OwnershipTransferCancelled"),
+            vec![(String::from("admin"), Key::cl_type())],
+        );
+        schemas.insert(
+            String::from("OwnershipTransferred"),
+            vec![
+                (String::from("previous_admin"), Key::cl_type()),
+                (String::from("new_admin"), Key::cl_type()),
+            ],
+        );
+        Schemas(schemas)
+    }
+}
+
+impl CLTyped for Schemas {
+    fn cl_type() -> CLType {
+        CLType::Any
+    }
+}
+
+impl ToBytes for Schemas {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
+    }
+}
+
+/// Creates the named keys required by the standard, must be called from the contract context.
+pub fn init() {
+    storage::new_dictionary(EVENTS_DICT).unwrap_or_revert();
+    runtime::put_key(EVENTS_LENGTH, storage::new_uref(0u32).into());
+    runtime::put_key(EVENTS_SCHEMA, storage::new_uref(Schemas::new()).into());
+    runtime::put_key(
+        CES_VERSION_KEY,
+        storage::new_uref(CES_VERSION.to_string()).into(),
+    );
+}
+
+/// Describes the events of the current code, for upgrades that add events.
+pub fn update_schema() {
+    storage::write(get_uref(EVENTS_SCHEMA), Schemas::new());
+}
+
+pub fn emit(event: Event) {
+    let events_length_uref = get_uref(EVENTS_LENGTH);
+    let events_length: u32 = storage::read(events_length_uref)
+        .unwrap_or_revert()
+        .unwrap_or_revert();
+    storage::dictionary_put(
+        get_uref(EVENTS_DICT),
+        &events_length.to_string(),
+        Bytes::from(event.serialize().unwrap_or_revert()),
+    );
+    storage::write(events_length_uref, events_length + 1);
+}
+
+fn get_uref(name: &str) -> URef {
+    *runtime::get_key(name)
+        .unwrap_or_revert()
+        .as_uref()
+        .unwrap_or_revert()
+}
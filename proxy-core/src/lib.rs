@@ -0,0 +1,903 @@
+//! Provider registry shared by the kyc and synth proxies. Both proxies keep an ordered list of
+//! provider contracts, manage them through the same roles and forward their checks to the active
+//! providers according to the aggregation policy. A proxy only describes what differs, the
+//! dictionaries, entry point names and checks, in a [`ProxyConfig`] and exports its entry points
+//! with [`export_entry_points!`].
+#![no_std]
+
+extern crate alloc;
+
+pub mod events;
+mod registry;
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    contracts::NamedKeys, runtime_args, CLType, CLTyped, CLValue, ContractHash,
+    ContractPackageHash, ContractVersion, EntryPoint, EntryPointAccess, EntryPointType,
+    EntryPoints, Key, Parameter, RuntimeArgs, URef,
+};
+use registry::{ProviderDict, Role};
+
+/// Describes a proxy built on the shared registry.
+pub struct ProxyConfig {
+    /// Name of the dictionary holding the package hash of the provider at every position.
+    pub index_dictionary: &'static str,
+    /// Name of the dictionary listing the active providers in evaluation order.
+    pub active_dictionary: &'static str,
+    /// Name of the dictionary holding the record of every provider: its ban, weight and pinned
+    /// version.
+    pub records_dictionary: &'static str,
+    /// Name of the dictionary the proxy code before the shared registry kept the providers in,
+    /// only read when upgrading such a proxy. `None` for proxies that never had one.
+    pub baseline_dictionary: Option<&'static str>,
+    /// Name of the dictionary holding the granted roles.
+    pub roles_dictionary: &'static str,
+    /// Name of the dictionary holding the accounts allowed or denied regardless of the providers.
+    pub overrides_dictionary: &'static str,
+    /// Name of the dictionary holding the cached approvals, `None` for proxies without checks
+    /// with a cache.
+    pub cache_dictionary: Option<&'static str>,
+    /// Used in the named keys put into the installing account, `{name}-{infix}_contract` etc.
+    pub named_keys_infix: &'static str,
+    pub add_provider: &'static str,
+    pub remove_provider: &'static str,
+    pub ban_provider: &'static str,
+    pub ban_provider_until: &'static str,
+    pub unban_provider: &'static str,
+    /// The checks forwarded to the providers.
+    pub checks: &'static [Check],
+    /// Calls made to a provider when it is added, for proxies whose checks are only known at
+    /// runtime. `None` probes the checks of the config with their `probe_args`.
+    pub probes: Option<fn() -> Vec<Probe>>,
+}
+
+/// A check the proxy forwards to its providers, e.g. `is_kyc_proved`. The proxy exposes it under
+/// the same name and calls the entry point of the same name on the providers.
+pub struct Check {
+    pub name: &'static str,
+    /// Name of the variant returning the provider that decided the outcome, if the proxy has one.
+    pub detailed: Option<&'static str>,
+    /// Parameters declared for the check and its detailed variant.
+    pub parameters: fn() -> Vec<Parameter>,
+    /// Reads the named args of the current call that are passed on to the providers.
+    pub forwarded_args: fn() -> RuntimeArgs,
+    /// Args for the call made to a provider when it is added, to check that it implements the
+    /// entry point.
+    pub probe_args: fn() -> RuntimeArgs,
+    /// Entry points caching approvals of the check, if the proxy has them.
+    pub cache: Option<Cache>,
+}
+
+/// Names of the entry points managing the approvals cached for a check. Both take the parameters
+/// of the check.
+pub struct Cache {
+    /// Runs the check and caches an approval, the check then answers from the cache until the
+    /// approval expires.
+    pub prove: &'static str,
+    /// Drops the cached approval, admin only.
+    pub invalidate: &'static str,
+}
+
+/// A call made to a provider when it is added, to check that it implements a check: the name of
+/// the check and the args it is called with.
+pub type Probe = (String, RuntimeArgs);
+
+fn probes(config: &ProxyConfig) -> Vec<Probe> {
+    match config.probes {
+        Some(probes) => probes(),
+        None => config
+            .checks
+            .iter()
+            .map(|check| (check.name.into(), (check.probe_args)()))
+            .collect(),
+    }
+}
+
+pub fn init(config: &ProxyConfig) {
+    ProviderDict::init(
+        config,
+        runtime::get_named_arg("initial_providers"),
+        &probes(config),
+    )
+}
+
+pub fn check(config: &ProxyConfig, check: &Check) {
+    let ret: bool = ProviderDict::open(config)
+        .check(check.name, &(check.forwarded_args)(), check.cache.is_some())
+        .is_some();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+pub fn check_detailed(config: &ProxyConfig, check: &Check) {
+    let ret: Option<ContractPackageHash> = ProviderDict::open(config).check(
+        check.name,
+        &(check.forwarded_args)(),
+        check.cache.is_some(),
+    );
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+pub fn prove_and_cache(config: &ProxyConfig, check: &Check) {
+    let ret: bool = ProviderDict::open(config)
+        .prove_and_cache(check.name, &(check.forwarded_args)())
+        .is_some();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+pub fn invalidate_cached_proof(config: &ProxyConfig, check: &Check) {
+    ProviderDict::open(config).invalidate_cached_approval(check.name, &(check.forwarded_args)())
+}
+
+pub fn flush_cache(config: &ProxyConfig) {
+    ProviderDict::open(config).flush_cache()
+}
+
+pub fn set_cache_ttl(config: &ProxyConfig) {
+    ProviderDict::open(config).set_cache_ttl(runtime::get_named_arg("ttl"))
+}
+
+pub fn get_cache_ttl(config: &ProxyConfig) {
+    let ret: Option<u64> = ProviderDict::open(config).cache_ttl();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// Calls `entry_point` with `args` on the active providers and returns whether the account is
+/// approved, for proxies whose checks are only known at runtime.
+pub fn forward(config: &ProxyConfig, entry_point: &str, args: RuntimeArgs) {
+    let ret: bool = ProviderDict::open(config)
+        .check(entry_point, &args, false)
+        .is_some();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// Like [`forward`], but returns the provider whose approval decided the outcome.
+pub fn forward_detailed(config: &ProxyConfig, entry_point: &str, args: RuntimeArgs) {
+    let ret: Option<ContractPackageHash> =
+        ProviderDict::open(config).check(entry_point, &args, false);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+pub fn add_provider(config: &ProxyConfig) {
+    ProviderDict::open(config).add_provider(runtime::get_named_arg("provider"), &probes(config))
+}
+
+pub fn remove_provider(config: &ProxyConfig) {
+    ProviderDict::open(config).remove_provider(runtime::get_named_arg("provider"))
+}
+
+pub fn set_provider_weight(config: &ProxyConfig) {
+    ProviderDict::open(config).set_provider_weight(
+        runtime::get_named_arg("provider"),
+        runtime::get_named_arg("weight"),
+    )
+}
+
+pub fn set_provider_version(config: &ProxyConfig) {
+    ProviderDict::open(config).set_provider_version(
+        runtime::get_named_arg("provider"),
+        runtime::get_named_arg("version"),
+    )
+}
+
+pub fn get_provider_version(config: &ProxyConfig) {
+    let ret: Option<ContractVersion> =
+        ProviderDict::open(config).get_provider_version(runtime::get_named_arg("provider"));
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+pub fn move_provider(config: &ProxyConfig) {
+    ProviderDict::open(config).move_provider(
+        runtime::get_named_arg("provider"),
+        runtime::get_named_arg("new_index"),
+    )
+}
+
+pub fn ban_provider(config: &ProxyConfig) {
+    ProviderDict::open(config).ban_provider(runtime::get_named_arg("provider"))
+}
+
+pub fn ban_provider_until(config: &ProxyConfig) {
+    ProviderDict::open(config).ban_provider_until(
+        runtime::get_named_arg("provider"),
+        runtime::get_named_arg("until"),
+    )
+}
+
+pub fn get_ban_expiry(config: &ProxyConfig) {
+    let ret: Option<u64> =
+        ProviderDict::open(config).get_ban_expiry(runtime::get_named_arg("provider"));
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+pub fn unban_provider(config: &ProxyConfig) {
+    ProviderDict::open(config).unban_provider(runtime::get_named_arg("provider"))
+}
+
+pub fn get_providers(config: &ProxyConfig) {
+    let offset = runtime::get_named_arg::<u64>("offset");
+    let limit = runtime::get_named_arg::<u64>("limit");
+    let ret: Vec<(ContractPackageHash, bool)> =
+        ProviderDict::open(config).get_providers(offset, limit);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+pub fn get_provider_count(config: &ProxyConfig) {
+    let ret: u64 = ProviderDict::open(config).len;
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+pub fn is_provider_active(config: &ProxyConfig) {
+    let ret: Option<bool> =
+        ProviderDict::open(config).is_provider_active(runtime::get_named_arg("provider"));
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+pub fn set_aggregation_policy(config: &ProxyConfig) {
+    ProviderDict::open(config).set_aggregation_policy(
+        runtime::get_named_arg("policy"),
+        runtime::get_named_arg("quorum"),
+    )
+}
+
+pub fn get_aggregation_policy(config: &ProxyConfig) {
+    let ret: (u8, u32) = ProviderDict::open(config).policy().into_parts();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+pub fn grant_role(config: &ProxyConfig) {
+    ProviderDict::open(config).grant_role(
+        runtime::get_named_arg("role"),
+        runtime::get_named_arg("account"),
+    )
+}
+
+pub fn revoke_role(config: &ProxyConfig) {
+    ProviderDict::open(config).revoke_role(
+        runtime::get_named_arg("role"),
+        runtime::get_named_arg("account"),
+    )
+}
+
+pub fn has_role(config: &ProxyConfig) {
+    let role = runtime::get_named_arg::<u8>("role");
+    let account = runtime::get_named_arg::<Key>("account");
+    let ret: bool = ProviderDict::open(config).has_role(Role::from_u8(role), account);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+pub fn pause(config: &ProxyConfig) {
+    ProviderDict::open(config).pause(runtime::get_named_arg("revert_checks"))
+}
+
+pub fn unpause(config: &ProxyConfig) {
+    ProviderDict::open(config).unpause()
+}
+
+pub fn is_paused(config: &ProxyConfig) {
+    let ret: bool = ProviderDict::open(config).paused().is_some();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+pub fn deny_account(config: &ProxyConfig) {
+    ProviderDict::open(config).deny_account(runtime::get_named_arg("account"))
+}
+
+pub fn allow_account(config: &ProxyConfig) {
+    ProviderDict::open(config).allow_account(runtime::get_named_arg("account"))
+}
+
+pub fn remove_denied_account(config: &ProxyConfig) {
+    ProviderDict::open(config).remove_denied_account(runtime::get_named_arg("account"))
+}
+
+pub fn remove_allowed_account(config: &ProxyConfig) {
+    ProviderDict::open(config).remove_allowed_account(runtime::get_named_arg("account"))
+}
+
+pub fn is_account_denied(config: &ProxyConfig) {
+    let ret: bool = ProviderDict::open(config).account_override(runtime::get_named_arg("account"))
+        == Some(false);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+pub fn is_account_allowed(config: &ProxyConfig) {
+    let ret: bool = ProviderDict::open(config).account_override(runtime::get_named_arg("account"))
+        == Some(true);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+pub fn migrate(config: &ProxyConfig) {
+    ProviderDict::migrate(config, runtime::get_named_arg("access_token"))
+}
+
+pub fn transfer_ownership(config: &ProxyConfig) {
+    ProviderDict::open(config).transfer_ownership(runtime::get_named_arg("new_admin"))
+}
+
+pub fn accept_ownership(config: &ProxyConfig) {
+    ProviderDict::open(config).accept_ownership()
+}
+
+pub fn cancel_ownership_transfer(config: &ProxyConfig) {
+    ProviderDict::open(config).cancel_ownership_transfer()
+}
+
+fn account_entry_point(name: &str, ret: CLType) -> EntryPoint {
+    EntryPoint::new(
+        name,
+        vec![Parameter::new("account", Key::cl_type())],
+        ret,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    )
+}
+
+fn provider_entry_point(name: &str) -> EntryPoint {
+    EntryPoint::new(
+        name,
+        vec![Parameter::new("provider", Key::cl_type())],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    )
+}
+
+/// Declares a check returning a bool and, if given, its detailed variant returning the provider that
+/// decided the outcome.
+pub fn add_check_entry_points(
+    entry_points: &mut EntryPoints,
+    name: &str,
+    detailed: Option<&str>,
+    parameters: Vec<Parameter>,
+) {
+    entry_points.add_entry_point(EntryPoint::new(
+        name,
+        parameters.clone(),
+        CLType::Bool,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    if let Some(detailed) = detailed {
+        entry_points.add_entry_point(EntryPoint::new(
+            detailed,
+            parameters,
+            Option::<ContractPackageHash>::cl_type(),
+            EntryPointAccess::Public,
+            EntryPointType::Contract,
+        ));
+    }
+}
+
+/// Declares the entry points of the proxy, the exported functions must match these names.
+pub fn entry_points(config: &ProxyConfig) -> EntryPoints {
+    let mut entry_points = EntryPoints::new();
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "init",
+        vec![
+            Parameter::new(
+                "initial_providers",
+                CLType::List(Box::new(ContractPackageHash::cl_type())),
+            ),
+            Parameter::new("access_token", URef::cl_type()),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    for check in config.checks {
+        add_check_entry_points(
+            &mut entry_points,
+            check.name,
+            check.detailed,
+            (check.parameters)(),
+        );
+        if let Some(cache) = &check.cache {
+            entry_points.add_entry_point(EntryPoint::new(
+                cache.prove,
+                (check.parameters)(),
+                CLType::Bool,
+                EntryPointAccess::Public,
+                EntryPointType::Contract,
+            ));
+            entry_points.add_entry_point(EntryPoint::new(
+                cache.invalidate,
+                (check.parameters)(),
+                CLType::Unit,
+                EntryPointAccess::Public,
+                EntryPointType::Contract,
+            ));
+        }
+    }
+
+    if config.checks.iter().any(|check| check.cache.is_some()) {
+        entry_points.add_entry_point(EntryPoint::new(
+            "flush_cache",
+            vec![],
+            CLType::Unit,
+            EntryPointAccess::Public,
+            EntryPointType::Contract,
+        ));
+
+        entry_points.add_entry_point(EntryPoint::new(
+            "set_cache_ttl",
+            vec![Parameter::new("ttl", Option::<u64>::cl_type())],
+            CLType::Unit,
+            EntryPointAccess::Public,
+            EntryPointType::Contract,
+        ));
+
+        entry_points.add_entry_point(EntryPoint::new(
+            "get_cache_ttl",
+            vec![],
+            Option::<u64>::cl_type(),
+            EntryPointAccess::Public,
+            EntryPointType::Contract,
+        ));
+    }
+
+    entry_points.add_entry_point(provider_entry_point(config.add_provider));
+    entry_points.add_entry_point(provider_entry_point(config.remove_provider));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_provider_weight",
+        vec![
+            Parameter::new("provider", Key::cl_type()),
+            Parameter::new("weight", CLType::U32),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_provider_version",
+        vec![
+            Parameter::new("provider", Key::cl_type()),
+            Parameter::new("version", Option::<ContractVersion>::cl_type()),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "get_provider_version",
+        vec![Parameter::new("provider", Key::cl_type())],
+        Option::<ContractVersion>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "move_provider",
+        vec![
+            Parameter::new("provider", Key::cl_type()),
+            Parameter::new("new_index", CLType::U64),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(provider_entry_point(config.ban_provider));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        config.ban_provider_until,
+        vec![
+            Parameter::new("provider", Key::cl_type()),
+            Parameter::new("until", CLType::U64),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(provider_entry_point(config.unban_provider));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "get_ban_expiry",
+        vec![Parameter::new("provider", Key::cl_type())],
+        Option::<u64>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "get_providers",
+        vec![
+            Parameter::new("offset", CLType::U64),
+            Parameter::new("limit", CLType::U64),
+        ],
+        Vec::<(ContractPackageHash, bool)>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "get_provider_count",
+        vec![],
+        CLType::U64,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "is_provider_active",
+        vec![Parameter::new("provider", Key::cl_type())],
+        Option::<bool>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_aggregation_policy",
+        vec![
+            Parameter::new("policy", CLType::U8),
+            Parameter::new("quorum", CLType::U32),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "get_aggregation_policy",
+        vec![],
+        <(u8, u32)>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "grant_role",
+        vec![
+            Parameter::new("role", CLType::U8),
+            Parameter::new("account", Key::cl_type()),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "revoke_role",
+        vec![
+            Parameter::new("role", CLType::U8),
+            Parameter::new("account", Key::cl_type()),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "has_role",
+        vec![
+            Parameter::new("role", CLType::U8),
+            Parameter::new("account", Key::cl_type()),
+        ],
+        CLType::Bool,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "pause",
+        vec![Parameter::new("revert_checks", CLType::Bool)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "unpause",
+        vec![],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "is_paused",
+        vec![],
+        CLType::Bool,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(account_entry_point("deny_account", CLType::Unit));
+    entry_points.add_entry_point(account_entry_point("allow_account", CLType::Unit));
+    entry_points.add_entry_point(account_entry_point("remove_denied_account", CLType::Unit));
+    entry_points.add_entry_point(account_entry_point("remove_allowed_account", CLType::Unit));
+    entry_points.add_entry_point(account_entry_point("is_account_denied", CLType::Bool));
+    entry_points.add_entry_point(account_entry_point("is_account_allowed", CLType::Bool));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "migrate",
+        vec![Parameter::new("access_token", URef::cl_type())],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "transfer_ownership",
+        vec![Parameter::new("new_admin", Key::cl_type())],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "accept_ownership",
+        vec![],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        "cancel_ownership_transfer",
+        vec![],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points
+}
+
+/// Installs the proxy, to be called from the `call` entry point of the proxy binary.
+pub fn install(config: &ProxyConfig) {
+    install_with(config, entry_points(config), NamedKeys::new())
+}
+
+/// Installs the proxy with additional entry points and named keys on top of the ones derived from
+/// the config, `entry_points` is usually built from [`entry_points`]. If the installing account
+/// already holds a proxy of the same name, a new version of it is added instead.
+pub fn install_with(config: &ProxyConfig, entry_points: EntryPoints, named_keys: NamedKeys) {
+    let proxy_name: String = runtime::get_named_arg("name");
+    let infix = config.named_keys_infix;
+
+    match runtime::get_key(&format!("{}-{}_package_hash", proxy_name, infix)) {
+        Some(package_key) => upgrade(config, &proxy_name, package_key, entry_points, named_keys),
+        None => install_new(config, &proxy_name, entry_points, named_keys),
+    }
+}
+
+fn install_new(
+    config: &ProxyConfig,
+    proxy_name: &str,
+    entry_points: EntryPoints,
+    mut named_keys: NamedKeys,
+) {
+    let (contract_package_hash, access_uref) = storage::create_contract_package_at_hash();
+    let infix = config.named_keys_infix;
+
+    named_keys.insert(
+        format!("{}-{}_contract_package", proxy_name, infix),
+        storage::new_uref(contract_package_hash).into(),
+    );
+    let (contract_hash, _) =
+        storage::add_contract_version(contract_package_hash, entry_points, named_keys);
+    runtime::put_key(
+        &format!("{}-{}_package_hash", proxy_name, infix),
+        contract_package_hash.into(),
+    );
+    runtime::put_key(
+        &format!("{}-{}_contract", proxy_name, infix),
+        contract_hash.into(),
+    );
+    runtime::put_key(
+        &format!("{}-{}_access_token", proxy_name, infix),
+        access_uref.into(),
+    );
+    // // Added for the testing convenience.
+    runtime::put_key(
+        &format!("{}-{}_contract_hash", proxy_name, infix),
+        storage::new_uref(contract_hash).into(),
+    );
+
+    let initial_providers =
+        match runtime::get_named_arg::<Option<Vec<ContractPackageHash>>>("initial_providers") {
+            Some(providers) => providers,
+            None => Vec::new(),
+        };
+
+    runtime::call_versioned_contract(
+        contract_package_hash,
+        None,
+        "init",
+        runtime_args! {
+            "initial_providers" => initial_providers,
+            "access_token" => access_uref
+        },
+    )
+}
+
+/// Adds a new version to the existing package using the access token held by the installing
+/// account. `add_contract_version` carries the named keys of the current version over, the
+/// dictionaries included, so the registry survives the upgrade and the new code migrates it with
+/// its `migrate` hook. Migrating is authorized by the access token, so the upgrade does not depend
+/// on who the admin is. Proxies installed by the code before the shared registry are upgraded
+/// the same way.
+fn upgrade(
+    config: &ProxyConfig,
+    proxy_name: &str,
+    package_key: Key,
+    entry_points: EntryPoints,
+    named_keys: NamedKeys,
+) {
+    let infix = config.named_keys_infix;
+    let contract_package_hash =
+        ContractPackageHash::new(package_key.into_hash().unwrap_or_revert());
+    let contract_key = format!("{}-{}_contract", proxy_name, infix);
+    let access_token = runtime::get_key(&format!("{}-{}_access_token", proxy_name, infix))
+        .unwrap_or_revert()
+        .into_uref()
+        .unwrap_or_revert();
+    let old_contract_hash = ContractHash::new(
+        runtime::get_key(&contract_key)
+            .unwrap_or_revert()
+            .into_hash()
+            .unwrap_or_revert(),
+    );
+
+    let (contract_hash, _) =
+        storage::add_contract_version(contract_package_hash, entry_points, named_keys);
+    // the old code must not write to the shared dictionaries anymore
+    storage::disable_contract_version(contract_package_hash, old_contract_hash).unwrap_or_revert();
+    runtime::put_key(&contract_key, contract_hash.into());
+    runtime::put_key(
+        &format!("{}-{}_contract_hash", proxy_name, infix),
+        storage::new_uref(contract_hash).into(),
+    );
+
+    runtime::call_versioned_contract(
+        contract_package_hash,
+        None,
+        "migrate",
+        runtime_args! {"access_token" => access_token},
+    )
+}
+
+/// Exports the entry points declared by [`entry_points`] for a proxy built on `config`, each
+/// calling the function of the same name in this crate. The provider management entry points are
+/// exported under the names given here, which must be the names in the config, and every check
+/// under its own name, its detailed variant and its cache entry points under theirs. The cache
+/// settings are exported by every proxy but only declared by proxies with a cache. The installer,
+/// `call`, is left to the binary.
+///
+/// ```ignore
+/// proxy_core::export_entry_points! {
+///     config: CONFIG,
+///     add_provider: add_kyc_provider,
+///     remove_provider: remove_kyc_provider,
+///     ban_provider: ban_provider,
+///     ban_provider_until: ban_provider_until,
+///     unban_provider: unban_provider,
+///     checks: [
+///         is_kyc_proved => IS_KYC_PROVED,
+///             detailed: is_kyc_proved_detailed,
+///             cache: (prove_and_cache, invalidate_cached_proof);
+///     ],
+/// }
+/// ```
+#[macro_export]
+macro_rules! export_entry_points {
+    (
+        config: $config:ident,
+        add_provider: $add_provider:ident,
+        remove_provider: $remove_provider:ident,
+        ban_provider: $ban_provider:ident,
+        ban_provider_until: $ban_provider_until:ident,
+        unban_provider: $unban_provider:ident,
+        checks: [$(
+            $check_name:ident => $check:ident
+            $(, detailed: $detailed:ident)?
+            $(, cache: ($prove:ident, $invalidate:ident))?
+        );* $(;)?] $(,)?
+    ) => {
+        $crate::export_entry_points!(@export $config, init);
+
+        $(
+            #[no_mangle]
+            pub extern "C" fn $check_name() {
+                $crate::check(&$config, &$check)
+            }
+
+            $(
+                #[no_mangle]
+                pub extern "C" fn $detailed() {
+                    $crate::check_detailed(&$config, &$check)
+                }
+            )?
+
+            $(
+                #[no_mangle]
+                pub extern "C" fn $prove() {
+                    $crate::prove_and_cache(&$config, &$check)
+                }
+
+                #[no_mangle]
+                pub extern "C" fn $invalidate() {
+                    $crate::invalidate_cached_proof(&$config, &$check)
+                }
+            )?
+        )*
+
+        $crate::export_entry_points!(@export $config, flush_cache, set_cache_ttl, get_cache_ttl);
+
+        #[no_mangle]
+        pub extern "C" fn $add_provider() {
+            $crate::add_provider(&$config)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn $remove_provider() {
+            $crate::remove_provider(&$config)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn $ban_provider() {
+            $crate::ban_provider(&$config)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn $ban_provider_until() {
+            $crate::ban_provider_until(&$config)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn $unban_provider() {
+            $crate::unban_provider(&$config)
+        }
+
+        $crate::export_entry_points!(
+            @export $config,
+            set_provider_weight,
+            set_provider_version,
+            get_provider_version,
+            move_provider,
+            get_ban_expiry,
+            get_providers,
+            get_provider_count,
+            is_provider_active,
+            set_aggregation_policy,
+            get_aggregation_policy,
+            grant_role,
+            revoke_role,
+            has_role,
+            pause,
+            unpause,
+            is_paused,
+            deny_account,
+            allow_account,
+            remove_denied_account,
+            remove_allowed_account,
+            is_account_denied,
+            is_account_allowed,
+            migrate,
+            transfer_ownership,
+            accept_ownership,
+            cancel_ownership_transfer
+        );
+    };
+    (@export $config:ident, $($name:ident),*) => {
+        $(
+            #[no_mangle]
+            pub extern "C" fn $name() {
+                $crate::$name(&$config)
+            }
+        )*
+    };
+}
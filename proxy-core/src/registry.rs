@@ -0,0 +1,1165 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use casper_contract::contract_api::runtime::call_versioned_contract;
+use casper_contract::contract_api::storage::{dictionary_get, dictionary_put};
+use casper_contract::{
+    contract_api::{
+        runtime::{self, revert},
+        storage::{self, new_dictionary},
+    },
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    system::CallStackElement,
+    ApiError, CLType, CLTyped, ContractPackageHash, ContractVersion, Key, RuntimeArgs, URef,
+    URefAddr,
+};
+
+use crate::events::{self, Event};
+use crate::{Probe, ProxyConfig};
+
+/// Version of the storage layout written by this code. Proxies installed by the code before it
+/// store no version, upgrading them migrates their providers to this layout.
+pub(crate) const SCHEMA_VERSION: u32 = 1;
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+// address of the access token of the package, the address alone grants nothing
+const ACCESS_TOKEN_KEY: &str = "access_token_addr";
+
+// registry settings, stored under named keys of the contract
+const LEN_KEY: &str = "provider_count";
+const ACTIVE_LEN_KEY: &str = "active_provider_count";
+const ADMIN_KEY: &str = "admin";
+const PENDING_ADMIN_KEY: &str = "pending_admin";
+const POLICY_KEY: &str = "policy";
+const PAUSED_KEY: &str = "paused";
+const CACHE_TTL_KEY: &str = "cache_ttl";
+const CACHE_EPOCH_KEY: &str = "cache_epoch";
+
+/// Permissions that can be granted on the registry. The admin recorded on install implicitly holds
+/// every role, and holders of `Role::Admin` implicitly hold all the others.
+#[derive(Clone, Copy)]
+pub(crate) enum Role {
+    /// Grants and revokes roles.
+    Admin = 0,
+    /// Bans and unbans providers, e.g. for incident response.
+    Operator = 1,
+    /// Adds, removes, reorders and weighs providers.
+    ProviderManager = 2,
+    /// Read-only role for compliance tooling, it does not unlock any entry point.
+    Auditor = 3,
+}
+
+impl Role {
+    pub(crate) fn from_u8(role: u8) -> Self {
+        match role {
+            0 => Role::Admin,
+            1 => Role::Operator,
+            2 => Role::ProviderManager,
+            3 => Role::Auditor,
+            _ => revert(ApiError::User(302)),
+        }
+    }
+}
+
+/// How the answers of the active providers are combined into a single result.
+#[derive(Clone, Copy)]
+pub(crate) enum Policy {
+    /// Approved as soon as one provider approves.
+    Any,
+    /// Approved only if every active provider approves.
+    All,
+    /// Approved as soon as the given number of providers approve.
+    AtLeast(u32),
+    /// Approved as soon as the weights of the approving providers add up to the threshold.
+    Weighted(u32),
+}
+
+impl Policy {
+    fn from_parts(policy: u8, quorum: u32) -> Self {
+        match policy {
+            0 => Policy::Any,
+            1 => Policy::All,
+            2 if quorum > 0 => Policy::AtLeast(quorum),
+            3 if quorum > 0 => Policy::Weighted(quorum),
+            _ => revert(ApiError::User(306)),
+        }
+    }
+
+    pub(crate) fn into_parts(self) -> (u8, u32) {
+        match self {
+            Policy::Any => (0, 0),
+            Policy::All => (1, 0),
+            Policy::AtLeast(quorum) => (2, quorum),
+            Policy::Weighted(threshold) => (3, threshold),
+        }
+    }
+}
+
+/// Everything the registry keeps about a provider: whether it is banned and until when, its
+/// weight and its pinned version. Serialized like the
+/// `(bool, Option<u64>, (u32, Option<ContractVersion>))` tuple of its fields, so clients can decode
+/// it without knowing the struct.
+#[derive(Clone, Copy)]
+pub(crate) struct ProviderRecord {
+    banned: bool,
+    /// Block time a ban lapses at, `None` for bans that last until the provider is unbanned.
+    banned_until: Option<u64>,
+    weight: u32,
+    version: Option<ContractVersion>,
+}
+
+type ProviderRecordParts = (bool, Option<u64>, (u32, Option<ContractVersion>));
+
+impl ProviderRecord {
+    /// Providers whose ban has lapsed are active again.
+    fn is_active(&self, blocktime: u64) -> bool {
+        !self.banned || self.banned_until.map_or(false, |until| until <= blocktime)
+    }
+
+    fn to_parts(self) -> ProviderRecordParts {
+        (self.banned, self.banned_until, (self.weight, self.version))
+    }
+}
+
+impl CLTyped for ProviderRecord {
+    fn cl_type() -> CLType {
+        ProviderRecordParts::cl_type()
+    }
+}
+
+impl ToBytes for ProviderRecord {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.to_parts().to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.to_parts().serialized_length()
+    }
+}
+
+impl FromBytes for ProviderRecord {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let ((banned, banned_until, (weight, version)), remainder) =
+            ProviderRecordParts::from_bytes(bytes)?;
+        Ok((
+            ProviderRecord {
+                banned,
+                banned_until,
+                weight,
+                version,
+            },
+            remainder,
+        ))
+    }
+}
+
+/// Entry of the active list: the provider, the block time its ban lapses at while a ban with an
+/// expiry is running, and its weight and pinned version. Such providers stay listed and are
+/// skipped until then. Copying the settings from the record lets a check read a single item per
+/// provider.
+type ActiveProvider = (
+    ContractPackageHash,
+    Option<u64>,
+    (u32, Option<ContractVersion>),
+);
+
+/// Approval kept in the cache: the block time it expires at, the cache epoch it was stored in and
+/// the provider that decided it.
+type CachedApproval = (u64, u64, ContractPackageHash);
+
+/// The index dictionary maps the hex encoded position of every provider to its package hash, the
+/// records dictionary holds one `Option<ProviderRecord>` per provider under its hex encoded
+/// package hash, `None` once the provider is removed. The active dictionary lists the active
+/// providers in evaluation order, so checks do not pay for banned ones. The number of providers
+/// and the other registry settings live in named keys.
+pub(crate) struct ProviderDict {
+    index: URef,
+    active: URef,
+    records: URef,
+    roles: URef,
+    overrides: URef,
+    cache: Option<URef>,
+    pub(crate) len: u64,
+}
+
+impl ProviderDict {
+    pub(crate) fn init(
+        config: &ProxyConfig,
+        initial_providers: Vec<ContractPackageHash>,
+        probes: &[Probe],
+    ) {
+        // `call()` runs `init` in the same deploy that installs the contract, so the flag is
+        // already set by the time anyone else can reach the entry point.
+        if runtime::has_key("initialized") {
+            revert(ApiError::User(305))
+        }
+        // read after the flag, so a second call reverts the same way whatever its args
+        let access_token: URef = runtime::get_named_arg("access_token");
+        Self::init_storage(config, access_token);
+        let registry = Self::open(config);
+        let mut len: u64 = 0;
+        for provider_package_hash in initial_providers {
+            let str_provider = Self::hash_item_key(provider_package_hash.value());
+            // like `add_provider`, a provider is only listed once
+            if registry.record(&str_provider).is_some() {
+                continue;
+            }
+            Self::assert_conforms(provider_package_hash, probes);
+            registry.set_provider_at(len, provider_package_hash);
+            registry.put_record(&str_provider, Self::NEW_RECORD);
+            len += 1;
+            events::emit(Event::ProviderAdded {
+                provider: provider_package_hash,
+            });
+        }
+        Self::put_setting(LEN_KEY, len);
+        registry.rebuild_active_providers(len);
+    }
+
+    /// Writes the storage of an empty registry administered by the caller.
+    fn init_storage(config: &ProxyConfig, access_token: URef) {
+        runtime::put_key("initialized", storage::new_uref(true).into());
+        Self::put_setting(ACCESS_TOKEN_KEY, access_token.addr());
+        Self::put_setting(SCHEMA_VERSION_KEY, SCHEMA_VERSION);
+        events::init();
+        new_dictionary(config.index_dictionary).unwrap_or_revert();
+        new_dictionary(config.active_dictionary).unwrap_or_revert();
+        new_dictionary(config.records_dictionary).unwrap_or_revert();
+        Self::put_setting(LEN_KEY, 0u64);
+        Self::put_setting(ACTIVE_LEN_KEY, 0u64);
+        // `init` and `migrate` are called from the installing session, so the caller is the
+        // installing account
+        Self::put_setting(ADMIN_KEY, Self::caller());
+        Self::put_setting(PENDING_ADMIN_KEY, Option::<Key>::None);
+        Self::put_setting(POLICY_KEY, Policy::Any.into_parts());
+        Self::put_setting(PAUSED_KEY, Option::<bool>::None);
+        new_dictionary(config.roles_dictionary).unwrap_or_revert();
+        new_dictionary(config.overrides_dictionary).unwrap_or_revert();
+        if let Some(cache_dictionary) = config.cache_dictionary {
+            new_dictionary(cache_dictionary).unwrap_or_revert();
+            Self::put_setting(CACHE_TTL_KEY, Option::<u64>::None);
+            Self::put_setting(CACHE_EPOCH_KEY, 0u64);
+        }
+    }
+
+    pub(crate) fn open(config: &ProxyConfig) -> Self {
+        let index = Self::named_uref(config.index_dictionary);
+        let active = Self::named_uref(config.active_dictionary);
+        let records = Self::named_uref(config.records_dictionary);
+        let roles = Self::named_uref(config.roles_dictionary);
+        let overrides = Self::named_uref(config.overrides_dictionary);
+        let cache = config.cache_dictionary.map(Self::named_uref);
+        let len: u64 = Self::setting(LEN_KEY);
+        ProviderDict {
+            index,
+            active,
+            records,
+            roles,
+            overrides,
+            cache,
+            len,
+        }
+    }
+
+    fn named_uref(name: &str) -> URef {
+        *runtime::get_key(name)
+            .unwrap_or_revert()
+            .as_uref()
+            .unwrap_or_revert()
+    }
+
+    fn setting<T: CLTyped + FromBytes>(name: &str) -> T {
+        storage::read(Self::named_uref(name))
+            .unwrap_or_revert()
+            .unwrap_or_revert()
+    }
+
+    fn put_setting<T: CLTyped + ToBytes>(name: &str, value: T) {
+        match runtime::get_key(name) {
+            Some(key) => storage::write(key.into_uref().unwrap_or_revert(), value),
+            None => runtime::put_key(name, storage::new_uref(value).into()),
+        }
+    }
+
+    pub(crate) fn add_provider(&self, provider_key: Key, probes: &[Probe]) {
+        self.assert_role(Role::ProviderManager);
+        let (provider_package_hash, str_provider) = Self::convert_provider_key(provider_key);
+        if self.record(&str_provider).is_none() {
+            Self::assert_conforms(provider_package_hash, probes);
+            self.set_provider_at(self.len, provider_package_hash);
+            self.put_record(&str_provider, Self::NEW_RECORD);
+            Self::put_setting(LEN_KEY, self.len + 1);
+            // the new provider is last in evaluation order, so it is appended to the active ones
+            let active_len: u64 = Self::setting(ACTIVE_LEN_KEY);
+            let active_provider = Self::active_provider(provider_package_hash, Self::NEW_RECORD);
+            dictionary_put(
+                self.active,
+                &Self::index_item_key(active_len),
+                active_provider,
+            );
+            Self::put_setting(ACTIVE_LEN_KEY, active_len + 1);
+            // under `Policy::All` the new provider can refuse accounts approved so far
+            self.expire_cache();
+            events::emit(Event::ProviderAdded {
+                provider: provider_package_hash,
+            });
+        }
+    }
+
+    /// Makes sure the provider implements the checks by making each probe call once. The entry
+    /// points of another contract cannot be read on chain, but a missing entry point, args the
+    /// provider does not accept or a result that is not a bool revert the call.
+    fn assert_conforms(provider_package_hash: ContractPackageHash, probes: &[Probe]) {
+        for (entry_point, args) in probes {
+            call_versioned_contract::<bool>(provider_package_hash, None, entry_point, args.clone());
+        }
+    }
+
+    pub(crate) fn remove_provider(&self, provider_key: Key) {
+        self.assert_role(Role::ProviderManager);
+        let (provider_package_hash, str_provider) = Self::convert_provider_key(provider_key);
+        if self.record(&str_provider).is_none() {
+            return;
+        }
+        let provider_index = self
+            .find_provider_index(provider_package_hash)
+            .unwrap_or_revert();
+        // shift the following providers down one slot so the indices stay dense and the order is
+        // kept, the stale last slot is past `len` and gets overwritten by the next `add_provider`
+        let last_index = self.len - 1;
+        for index in provider_index..last_index {
+            let next_provider_package_hash = self.provider_at(index + 1);
+            self.set_provider_at(index, next_provider_package_hash);
+        }
+        // dictionary items cannot be deleted, clearing the record lets the provider be re-added
+        dictionary_put(self.records, &str_provider, Option::<ProviderRecord>::None);
+        Self::put_setting(LEN_KEY, last_index);
+        self.rebuild_active_providers(last_index);
+        self.expire_cache();
+        events::emit(Event::ProviderRemoved {
+            provider: provider_package_hash,
+        });
+    }
+
+    /// Moves the provider to `new_index`, shifting the providers in between by one slot. Providers
+    /// are asked in index order, so cheap and reliable providers should come first.
+    pub(crate) fn move_provider(&self, provider_key: Key, new_index: u64) {
+        self.assert_role(Role::ProviderManager);
+        let (provider_package_hash, _) = Self::convert_provider_key(provider_key);
+        if new_index >= self.len {
+            revert(ApiError::User(308))
+        }
+        let old_index = self
+            .find_provider_index(provider_package_hash)
+            .unwrap_or_revert_with(ApiError::User(307));
+        if old_index < new_index {
+            for provider_index in old_index..new_index {
+                let next_provider_package_hash = self.provider_at(provider_index + 1);
+                self.set_provider_at(provider_index, next_provider_package_hash);
+            }
+        } else {
+            for provider_index in (new_index + 1..=old_index).rev() {
+                let previous_provider_package_hash = self.provider_at(provider_index - 1);
+                self.set_provider_at(provider_index, previous_provider_package_hash);
+            }
+        }
+        self.set_provider_at(new_index, provider_package_hash);
+        self.rebuild_active_providers(self.len);
+        // another provider may now decide the outcome
+        self.expire_cache();
+        events::emit(Event::ProviderMoved {
+            provider: provider_package_hash,
+            new_index,
+        });
+    }
+
+    /// Bans the provider until it is unbanned, a running ban with an expiry no longer lapses.
+    pub(crate) fn ban_provider(&self, provider_key: Key) {
+        self.assert_role(Role::Operator);
+        let (provider_package_hash, str_provider) = Self::convert_provider_key(provider_key);
+        if let Some(record) = self.record(&str_provider) {
+            if !record.banned || record.banned_until.is_some() {
+                self.ban(provider_package_hash, &str_provider, record, None)
+            }
+        }
+    }
+
+    /// Bans the provider until the block time `until` in milliseconds, the provider is asked again
+    /// by the first check from then on. Replaces a running ban, `until` has to lie in the future.
+    pub(crate) fn ban_provider_until(&self, provider_key: Key, until: u64) {
+        self.assert_role(Role::Operator);
+        let (provider_package_hash, str_provider) = Self::convert_provider_key(provider_key);
+        let record = self.registered_record(&str_provider);
+        if until <= Self::blocktime() {
+            revert(ApiError::User(314))
+        }
+        self.ban(provider_package_hash, &str_provider, record, Some(until));
+    }
+
+    fn ban(
+        &self,
+        provider_package_hash: ContractPackageHash,
+        str_provider: &str,
+        record: ProviderRecord,
+        until: Option<u64>,
+    ) {
+        let record = ProviderRecord {
+            banned: true,
+            banned_until: until,
+            ..record
+        };
+        self.put_record(str_provider, record);
+        self.rebuild_active_providers(self.len);
+        // approvals of the banned provider must not outlive it
+        self.expire_cache();
+        match until {
+            Some(until) => events::emit(Event::ProviderBannedUntil {
+                provider: provider_package_hash,
+                until,
+            }),
+            None => events::emit(Event::ProviderBanned {
+                provider: provider_package_hash,
+            }),
+        }
+    }
+
+    /// Returns the block time the ban of the provider lapses at, `None` if the provider is active,
+    /// its ban has lapsed or it is banned until it is unbanned.
+    pub(crate) fn get_ban_expiry(&self, provider_key: Key) -> Option<u64> {
+        let (_, str_provider) = Self::convert_provider_key(provider_key);
+        let record = self.registered_record(&str_provider);
+        if record.is_active(Self::blocktime()) {
+            None
+        } else {
+            record.banned_until
+        }
+    }
+
+    pub(crate) fn unban_provider(&self, provider_key: Key) {
+        self.assert_role(Role::Operator);
+        let (provider_package_hash, str_provider) = Self::convert_provider_key(provider_key);
+        match self.record(&str_provider) {
+            Some(record) if record.banned => {
+                let record = ProviderRecord {
+                    banned: false,
+                    banned_until: None,
+                    ..record
+                };
+                self.put_record(&str_provider, record);
+                self.rebuild_active_providers(self.len);
+                self.expire_cache();
+                events::emit(Event::ProviderUnbanned {
+                    provider: provider_package_hash,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn set_provider_weight(&self, provider_key: Key, weight: u32) {
+        self.assert_role(Role::ProviderManager);
+        let (provider_package_hash, str_provider) = Self::convert_provider_key(provider_key);
+        let record = ProviderRecord {
+            weight,
+            ..self.registered_record(&str_provider)
+        };
+        self.put_record(&str_provider, record);
+        self.rebuild_active_providers(self.len);
+        self.expire_cache();
+        events::emit(Event::ProviderWeightSet {
+            provider: provider_package_hash,
+            weight,
+        });
+    }
+
+    /// Record of newly added providers: active, weight 1 and no pinned version.
+    const NEW_RECORD: ProviderRecord = ProviderRecord {
+        banned: false,
+        banned_until: None,
+        weight: 1,
+        version: None,
+    };
+
+    /// Returns `None` for unknown or removed providers.
+    fn record(&self, str_provider: &str) -> Option<ProviderRecord> {
+        dictionary_get::<Option<ProviderRecord>>(self.records, str_provider)
+            .unwrap_or_revert()
+            .flatten()
+    }
+
+    /// The record of a registered provider, reverts with `ApiError::User(307)` for others.
+    fn registered_record(&self, str_provider: &str) -> ProviderRecord {
+        self.record(str_provider)
+            .unwrap_or_revert_with(ApiError::User(307))
+    }
+
+    fn put_record(&self, str_provider: &str, record: ProviderRecord) {
+        dictionary_put(self.records, str_provider, Some(record));
+    }
+
+    /// Pins the version of the provider's package that is called, `None` calls the latest version.
+    pub(crate) fn set_provider_version(&self, provider_key: Key, version: Option<ContractVersion>) {
+        self.assert_role(Role::Admin);
+        let (provider_package_hash, str_provider) = Self::convert_provider_key(provider_key);
+        let record = ProviderRecord {
+            version,
+            ..self.registered_record(&str_provider)
+        };
+        self.put_record(&str_provider, record);
+        self.rebuild_active_providers(self.len);
+        self.expire_cache();
+        events::emit(Event::ProviderVersionSet {
+            provider: provider_package_hash,
+            version,
+        });
+    }
+
+    pub(crate) fn get_provider_version(&self, provider_key: Key) -> Option<ContractVersion> {
+        let (_, str_provider) = Self::convert_provider_key(provider_key);
+        self.registered_record(&str_provider).version
+    }
+
+    /// Returns `Some(true)` for active, `Some(false)` for banned and `None` for unknown or removed
+    /// providers. Providers whose ban has lapsed are active again.
+    fn provider_status(&self, str_provider: &str) -> Option<bool> {
+        self.record(str_provider)
+            .map(|record| record.is_active(Self::blocktime()))
+    }
+
+    fn provider_at(&self, provider_index: u64) -> ContractPackageHash {
+        dictionary_get(self.index, &Self::index_item_key(provider_index))
+            .unwrap_or_revert()
+            .unwrap_or_revert()
+    }
+
+    fn set_provider_at(&self, provider_index: u64, provider_package_hash: ContractPackageHash) {
+        dictionary_put(
+            self.index,
+            &Self::index_item_key(provider_index),
+            provider_package_hash,
+        );
+    }
+
+    /// Lists the active providers among the first `len` in evaluation order, along with the
+    /// providers whose ban lapses on its own. Rewriting the list costs a pass over the registry,
+    /// which is paid by the rare management calls instead of every check.
+    fn rebuild_active_providers(&self, len: u64) {
+        let mut active_len: u64 = 0;
+        for provider_index in 0..len {
+            let provider_package_hash = self.provider_at(provider_index);
+            let record = match self.record(&Self::hash_item_key(provider_package_hash.value())) {
+                Some(record) => record,
+                None => continue,
+            };
+            // banned until unbanned
+            if record.banned && record.banned_until.is_none() {
+                continue;
+            }
+            let active_provider = Self::active_provider(provider_package_hash, record);
+            dictionary_put(
+                self.active,
+                &Self::index_item_key(active_len),
+                active_provider,
+            );
+            active_len += 1;
+        }
+        Self::put_setting(ACTIVE_LEN_KEY, active_len);
+    }
+
+    fn active_provider(
+        provider_package_hash: ContractPackageHash,
+        record: ProviderRecord,
+    ) -> ActiveProvider {
+        let banned_until = if record.banned {
+            record.banned_until
+        } else {
+            None
+        };
+        (
+            provider_package_hash,
+            banned_until,
+            (record.weight, record.version),
+        )
+    }
+
+    fn find_provider_index(&self, provider_package_hash: ContractPackageHash) -> Option<u64> {
+        (0..self.len)
+            .find(|provider_index| self.provider_at(*provider_index) == provider_package_hash)
+    }
+
+    /// Returns up to `limit` providers starting at `offset`, in evaluation order, along with
+    /// whether they are active.
+    pub(crate) fn get_providers(
+        &self,
+        offset: u64,
+        limit: u64,
+    ) -> Vec<(ContractPackageHash, bool)> {
+        let end = offset.saturating_add(limit).min(self.len);
+        (offset..end)
+            .map(|provider_index| {
+                let provider_package_hash = self.provider_at(provider_index);
+                let active = self
+                    .provider_status(&Self::hash_item_key(provider_package_hash.value()))
+                    .unwrap_or_revert();
+                (provider_package_hash, active)
+            })
+            .collect()
+    }
+
+    /// Returns `None` for providers that are not registered.
+    pub(crate) fn is_provider_active(&self, provider_key: Key) -> Option<bool> {
+        let (_, str_provider) = Self::convert_provider_key(provider_key);
+        self.provider_status(&str_provider)
+    }
+
+    pub(crate) fn grant_role(&self, role: u8, account: Key) {
+        self.assert_role(Role::Admin);
+        let role = Role::from_u8(role);
+        let account_item_key =
+            Self::account_item_key(account).unwrap_or_revert_with(ApiError::User(303));
+        let roles = self.granted_roles(&account_item_key);
+        dictionary_put(self.roles, &account_item_key, roles | Self::role_bit(role));
+        events::emit(Event::RoleGranted {
+            role: role as u8,
+            account,
+        });
+    }
+
+    pub(crate) fn revoke_role(&self, role: u8, account: Key) {
+        self.assert_role(Role::Admin);
+        let role = Role::from_u8(role);
+        let account_item_key =
+            Self::account_item_key(account).unwrap_or_revert_with(ApiError::User(303));
+        let roles = self.granted_roles(&account_item_key);
+        if roles & Self::role_bit(role) != 0 {
+            dictionary_put(self.roles, &account_item_key, roles & !Self::role_bit(role));
+            events::emit(Event::RoleRevoked {
+                role: role as u8,
+                account,
+            });
+        }
+    }
+
+    pub(crate) fn has_role(&self, role: Role, account: Key) -> bool {
+        self.admin() == account
+            || self.is_granted(Role::Admin, account)
+            || self.is_granted(role, account)
+    }
+
+    fn is_granted(&self, role: Role, account: Key) -> bool {
+        match Self::account_item_key(account) {
+            Some(account_item_key) => {
+                self.granted_roles(&account_item_key) & Self::role_bit(role) != 0
+            }
+            None => false,
+        }
+    }
+
+    /// Roles granted to an account, one bit per role.
+    fn granted_roles(&self, account_item_key: &str) -> u8 {
+        dictionary_get::<u8>(self.roles, account_item_key)
+            .unwrap_or_revert()
+            .unwrap_or_default()
+    }
+
+    fn role_bit(role: Role) -> u8 {
+        1 << role as u8
+    }
+
+    fn assert_role(&self, role: Role) {
+        if !self.has_role(role, Self::caller()) {
+            revert(ApiError::User(301))
+        }
+    }
+
+    pub(crate) fn transfer_ownership(&self, new_admin: Key) {
+        self.assert_admin();
+        match new_admin {
+            Key::Account(_) | Key::Hash(_) => {}
+            _ => revert(ApiError::User(303)),
+        }
+        Self::put_setting(PENDING_ADMIN_KEY, Some(new_admin));
+        events::emit(Event::OwnershipTransferStarted {
+            admin: self.admin(),
+            pending_admin: new_admin,
+        });
+    }
+
+    pub(crate) fn accept_ownership(&self) {
+        let caller = Self::caller();
+        if self.pending_admin() != Some(caller) {
+            revert(ApiError::User(304))
+        }
+        let previous_admin = self.admin();
+        Self::put_setting(ADMIN_KEY, caller);
+        Self::put_setting(PENDING_ADMIN_KEY, Option::<Key>::None);
+        events::emit(Event::OwnershipTransferred {
+            previous_admin,
+            new_admin: caller,
+        });
+    }
+
+    pub(crate) fn cancel_ownership_transfer(&self) {
+        self.assert_admin();
+        Self::put_setting(PENDING_ADMIN_KEY, Option::<Key>::None);
+        events::emit(Event::OwnershipTransferCancelled {
+            admin: self.admin(),
+        });
+    }
+
+    fn admin(&self) -> Key {
+        Self::setting(ADMIN_KEY)
+    }
+
+    fn pending_admin(&self) -> Option<Key> {
+        Self::setting(PENDING_ADMIN_KEY)
+    }
+
+    /// Brings the storage carried over by an upgrade to the current layout. Called by the
+    /// installer right after the new version is added.
+    pub(crate) fn migrate(config: &ProxyConfig, access_token: URef) {
+        if runtime::has_key(SCHEMA_VERSION_KEY) {
+            Self::assert_access_token(access_token);
+            let schema_version: u32 = Self::setting(SCHEMA_VERSION_KEY);
+            // downgrading would leave storage the code does not understand
+            if schema_version > SCHEMA_VERSION {
+                revert(ApiError::User(311))
+            }
+        } else {
+            Self::migrate_from_baseline(config, access_token);
+        }
+        events::update_schema();
+        Self::put_setting(SCHEMA_VERSION_KEY, SCHEMA_VERSION);
+    }
+
+    /// The code before the registry stored a schema version kept every provider in one dictionary:
+    /// the package hash under the decimal index, whether it is active under the hex encoded
+    /// package hash and the number of providers under `len`. It had no admin, so the account
+    /// upgrading it becomes the admin. That code had no `migrate` entry point, so nobody can reach
+    /// this before the installer does. The old dictionary is left behind unused.
+    fn migrate_from_baseline(config: &ProxyConfig, access_token: URef) {
+        let providers = Self::named_uref(config.baseline_dictionary.unwrap_or_revert());
+        let baseline_len: u64 = dictionary_get(providers, "len")
+            .unwrap_or_revert()
+            .unwrap_or_revert();
+        Self::init_storage(config, access_token);
+        let registry = Self::open(config);
+        let mut len: u64 = 0;
+        for provider_index in 0..baseline_len {
+            let provider_package_hash: ContractPackageHash =
+                dictionary_get(providers, &provider_index.to_string())
+                    .unwrap_or_revert()
+                    .unwrap_or_revert();
+            let str_provider = Self::hash_item_key(provider_package_hash.value());
+            // the baseline `init` listed duplicate initial providers twice
+            if registry.record(&str_provider).is_some() {
+                continue;
+            }
+            let active: bool = dictionary_get(providers, &str_provider)
+                .unwrap_or_revert()
+                .unwrap_or_revert();
+            registry.set_provider_at(len, provider_package_hash);
+            let record = ProviderRecord {
+                banned: !active,
+                ..Self::NEW_RECORD
+            };
+            registry.put_record(&str_provider, record);
+            len += 1;
+        }
+        Self::put_setting(LEN_KEY, len);
+        registry.rebuild_active_providers(len);
+    }
+
+    /// Upgrades are authorized by the access token of the package rather than by the admin, so
+    /// the installing account can still upgrade after handing the proxy over. The host refuses
+    /// URefs the caller does not hold, so a matching address proves possession of the token.
+    fn assert_access_token(access_token: URef) {
+        let access_token_addr: URefAddr = Self::setting(ACCESS_TOKEN_KEY);
+        if access_token.addr() != access_token_addr {
+            revert(ApiError::User(301))
+        }
+    }
+
+    fn assert_admin(&self) {
+        if self.admin() != Self::caller() {
+            revert(ApiError::User(301))
+        }
+    }
+
+    /// Refuses the account regardless of what the providers answer.
+    pub(crate) fn deny_account(&self, account: Key) {
+        self.assert_role(Role::Admin);
+        let account_item_key =
+            Self::account_item_key(account).unwrap_or_revert_with(ApiError::User(303));
+        dictionary_put(self.overrides, &account_item_key, Some(false));
+        events::emit(Event::AccountDenied { account });
+    }
+
+    /// Approves the account regardless of what the providers answer, e.g. for a treasury contract.
+    pub(crate) fn allow_account(&self, account: Key) {
+        self.assert_role(Role::Admin);
+        let account_item_key =
+            Self::account_item_key(account).unwrap_or_revert_with(ApiError::User(303));
+        dictionary_put(self.overrides, &account_item_key, Some(true));
+        events::emit(Event::AccountAllowed { account });
+    }
+
+    pub(crate) fn remove_denied_account(&self, account: Key) {
+        self.remove_account_override(account, false)
+    }
+
+    pub(crate) fn remove_allowed_account(&self, account: Key) {
+        self.remove_account_override(account, true)
+    }
+
+    fn remove_account_override(&self, account: Key, allowed: bool) {
+        self.assert_role(Role::Admin);
+        if self.account_override(account) == Some(allowed) {
+            let account_item_key = Self::account_item_key(account).unwrap_or_revert();
+            dictionary_put(self.overrides, &account_item_key, Option::<bool>::None);
+            events::emit(Event::AccountOverrideRemoved { account });
+        }
+    }
+
+    /// Returns `Some(true)` for allowed, `Some(false)` for denied and `None` for accounts the
+    /// providers decide on.
+    pub(crate) fn account_override(&self, account: Key) -> Option<bool> {
+        let account_item_key = Self::account_item_key(account)?;
+        dictionary_get::<Option<bool>>(self.overrides, &account_item_key)
+            .unwrap_or_revert()
+            .flatten()
+    }
+
+    /// Package hash of this contract, reported as the deciding provider for allowed accounts.
+    fn own_package_hash() -> ContractPackageHash {
+        match runtime::get_call_stack().last().unwrap_or_revert() {
+            CallStackElement::StoredContract {
+                contract_package_hash,
+                ..
+            } => *contract_package_hash,
+            _ => revert(ApiError::InvalidCallerInfoRequest),
+        }
+    }
+
+    /// Returns the immediate caller, the account for session code or the package hash (as
+    /// `Key::Hash`) when called from another contract, e.g. a multisig owning the proxy.
+    fn caller() -> Key {
+        let call_stack = runtime::get_call_stack();
+        // the last element is this contract, the one before it is whoever called it
+        match call_stack.iter().nth_back(1).unwrap_or_revert() {
+            CallStackElement::Session { account_hash }
+            | CallStackElement::StoredSession { account_hash, .. } => Key::Account(*account_hash),
+            CallStackElement::StoredContract {
+                contract_package_hash,
+                ..
+            } => Key::Hash(contract_package_hash.value()),
+        }
+    }
+
+    /// Accounts and contract packages are keyed by their hex encoded hash, which stays well below
+    /// the 128 byte limit of dictionary item keys.
+    fn account_item_key(account: Key) -> Option<String> {
+        match account {
+            Key::Account(account_hash) => Some(Self::hash_item_key(account_hash.value())),
+            Key::Hash(hash) => Some(Self::hash_item_key(hash)),
+            _ => None,
+        }
+    }
+
+    /// Lowercase hex encoding of a 32 byte hash, as displayed by the hash types.
+    fn hash_item_key(hash: [u8; 32]) -> String {
+        ContractPackageHash::new(hash).to_string()
+    }
+
+    fn index_item_key(provider_index: u64) -> String {
+        format!("{:x}", provider_index)
+    }
+
+    fn convert_provider_key(provider_key: Key) -> (ContractPackageHash, String) {
+        let provider_package_hash = match provider_key {
+            Key::Hash(provider_hash) => ContractPackageHash::from(provider_hash),
+            _ => revert(ApiError::User(300)),
+        };
+        (
+            provider_package_hash,
+            Self::hash_item_key(provider_package_hash.value()),
+        )
+    }
+
+    /// Forwards the check to the providers, returns the provider whose approval decided the
+    /// outcome or `None` if the account is not approved. Accounts on the allowlist are approved
+    /// by the proxy itself, accounts on the denylist are refused without asking the providers.
+    /// While the proxy is paused every account is refused, or the check reverts if the pause was
+    /// requested that way. With `cached` set an unexpired cached approval is returned before
+    /// asking the providers.
+    pub(crate) fn check(
+        &self,
+        entry_point: &str,
+        args: &RuntimeArgs,
+        cached: bool,
+    ) -> Option<ContractPackageHash> {
+        if let Some(revert_checks) = self.paused() {
+            if revert_checks {
+                revert(ApiError::User(310))
+            }
+            return None;
+        }
+        match Self::account_arg(args).and_then(|account| self.account_override(account)) {
+            Some(true) => Some(Self::own_package_hash()),
+            Some(false) => None,
+            None => {
+                if cached {
+                    if let Some(provider_package_hash) = self.cached_approval(entry_point, args) {
+                        return Some(provider_package_hash);
+                    }
+                }
+                self.evaluate(|provider_package_hash, version| {
+                    Self::check_single(provider_package_hash, version, entry_point, args)
+                })
+            }
+        }
+    }
+
+    fn account_arg(args: &RuntimeArgs) -> Option<Key> {
+        args.get("account")
+            .and_then(|account| account.clone().into_t::<Key>().ok())
+    }
+
+    /// Asks the providers like `check` and keeps an approval for the cache TTL, so `check` can
+    /// answer for the same args without calling the providers. Nothing is cached while the TTL is
+    /// not set, and approvals of allowed accounts are left to the allowlist.
+    pub(crate) fn prove_and_cache(
+        &self,
+        entry_point: &str,
+        args: &RuntimeArgs,
+    ) -> Option<ContractPackageHash> {
+        let approval = self.check(entry_point, args, false);
+        let overridden = Self::account_arg(args)
+            .and_then(|account| self.account_override(account))
+            .is_some();
+        if let (Some(provider_package_hash), Some(ttl), false) =
+            (approval, self.cache_ttl(), overridden)
+        {
+            let blocktime = Self::blocktime();
+            let mut expires_at = blocktime.saturating_add(ttl);
+            // a provider whose ban lapses is asked again and may refuse the account
+            if let Some(lapse) = self.next_ban_lapse(blocktime) {
+                expires_at = expires_at.min(lapse);
+            }
+            let cached_approval: CachedApproval = (
+                expires_at,
+                Self::setting(CACHE_EPOCH_KEY),
+                provider_package_hash,
+            );
+            dictionary_put(
+                self.cache_uref(),
+                &Self::cache_item_key(entry_point, args),
+                Some(cached_approval),
+            );
+        }
+        approval
+    }
+
+    /// Earliest block time a running ban among the active providers lapses at.
+    fn next_ban_lapse(&self, blocktime: u64) -> Option<u64> {
+        let active_len: u64 = Self::setting(ACTIVE_LEN_KEY);
+        (0..active_len)
+            .filter_map(|active_index| {
+                let (_, banned_until, _): ActiveProvider =
+                    dictionary_get(self.active, &Self::index_item_key(active_index))
+                        .unwrap_or_revert()
+                        .unwrap_or_revert();
+                banned_until.filter(|until| blocktime < *until)
+            })
+            .min()
+    }
+
+    /// Returns the provider of a cached approval for the args that has not expired yet.
+    fn cached_approval(
+        &self,
+        entry_point: &str,
+        args: &RuntimeArgs,
+    ) -> Option<ContractPackageHash> {
+        let (expires_at, epoch, provider_package_hash) = dictionary_get::<Option<CachedApproval>>(
+            self.cache_uref(),
+            &Self::cache_item_key(entry_point, args),
+        )
+        .unwrap_or_revert()
+        .flatten()?;
+        let cache_epoch: u64 = Self::setting(CACHE_EPOCH_KEY);
+        if epoch == cache_epoch && Self::blocktime() < expires_at {
+            Some(provider_package_hash)
+        } else {
+            None
+        }
+    }
+
+    /// Drops the cached approval for the args.
+    pub(crate) fn invalidate_cached_approval(&self, entry_point: &str, args: &RuntimeArgs) {
+        self.assert_role(Role::Admin);
+        let cache_item_key = Self::cache_item_key(entry_point, args);
+        dictionary_put(
+            self.cache_uref(),
+            &cache_item_key,
+            Option::<CachedApproval>::None,
+        );
+        events::emit(Event::CacheEntryInvalidated {
+            entry: cache_item_key,
+        });
+    }
+
+    /// Drops every cached approval.
+    pub(crate) fn flush_cache(&self) {
+        self.assert_role(Role::Admin);
+        if let Some(epoch) = self.expire_cache() {
+            events::emit(Event::CacheFlushed { epoch });
+        }
+    }
+
+    /// How long approvals are cached in milliseconds, `None` disables the cache. Changing it drops
+    /// the cached approvals.
+    pub(crate) fn set_cache_ttl(&self, ttl: Option<u64>) {
+        self.assert_role(Role::Admin);
+        if ttl == Some(0) {
+            revert(ApiError::User(313))
+        }
+        Self::put_setting(CACHE_TTL_KEY, ttl);
+        self.expire_cache();
+        events::emit(Event::CacheTtlSet { ttl });
+    }
+
+    pub(crate) fn cache_ttl(&self) -> Option<u64> {
+        Self::setting(CACHE_TTL_KEY)
+    }
+
+    /// Starts a new cache epoch, which invalidates the approvals cached so far without touching
+    /// them. Called by every change that can alter the outcome of a check. Returns the new epoch,
+    /// or `None` for proxies without a cache.
+    fn expire_cache(&self) -> Option<u64> {
+        self.cache?;
+        let epoch = Self::setting::<u64>(CACHE_EPOCH_KEY) + 1;
+        Self::put_setting(CACHE_EPOCH_KEY, epoch);
+        Some(epoch)
+    }
+
+    /// The cache dictionary, the cache entry points are only exported by proxies that have one.
+    fn cache_uref(&self) -> URef {
+        self.cache.unwrap_or_revert()
+    }
+
+    /// Hex encoded hash of the check and its args, which identifies a cached approval.
+    fn cache_item_key(entry_point: &str, args: &RuntimeArgs) -> String {
+        let mut bytes = entry_point.to_bytes().unwrap_or_revert();
+        bytes.append(&mut args.to_bytes().unwrap_or_revert());
+        Self::hash_item_key(runtime::blake2b(bytes))
+    }
+
+    fn blocktime() -> u64 {
+        u64::from(runtime::get_blocktime())
+    }
+
+    /// Asks the active providers in order and combines their answers according to the stored
+    /// policy, returning as soon as the outcome is decided. Returns the provider whose approval
+    /// decided the outcome, or `None` if the account is not approved.
+    fn evaluate<F: Fn(ContractPackageHash, Option<ContractVersion>) -> bool>(
+        &self,
+        approves: F,
+    ) -> Option<ContractPackageHash> {
+        let policy = self.policy();
+        let mut approvals: u64 = 0;
+        let mut score: u64 = 0;
+        let mut last_approval: Option<ContractPackageHash> = None;
+        let active_len: u64 = Self::setting(ACTIVE_LEN_KEY);
+        let blocktime = Self::blocktime();
+        for active_index in 0..active_len {
+            let (provider_package_hash, banned_until, (weight, version)): ActiveProvider =
+                dictionary_get(self.active, &Self::index_item_key(active_index))
+                    .unwrap_or_revert()
+                    .unwrap_or_revert();
+            // a provider whose ban has not lapsed yet neither approves nor refuses
+            if banned_until.map_or(false, |until| blocktime < until) {
+                continue;
+            }
+            let approved = approves(provider_package_hash, version);
+            if approved {
+                last_approval = Some(provider_package_hash);
+            }
+            match policy {
+                Policy::Any if approved => return last_approval,
+                Policy::All if !approved => return None,
+                Policy::AtLeast(quorum) if approved => {
+                    approvals += 1;
+                    if approvals >= u64::from(quorum) {
+                        return last_approval;
+                    }
+                }
+                Policy::Weighted(threshold) if approved => {
+                    score += u64::from(weight);
+                    if score >= u64::from(threshold) {
+                        return last_approval;
+                    }
+                }
+                _ => {}
+            }
+            // give up once the remaining providers cannot reach the quorum anymore
+            if let Policy::AtLeast(quorum) = policy {
+                if approvals + (active_len - active_index - 1) < u64::from(quorum) {
+                    return None;
+                }
+            }
+        }
+        match policy {
+            // every active provider approved, an empty registry approves no one
+            Policy::All => last_approval,
+            // if all available providers refused, return `None`
+            _ => None,
+        }
+    }
+
+    pub(crate) fn policy(&self) -> Policy {
+        let (policy, quorum): (u8, u32) = Self::setting(POLICY_KEY);
+        Policy::from_parts(policy, quorum)
+    }
+
+    pub(crate) fn set_aggregation_policy(&self, policy: u8, quorum: u32) {
+        self.assert_role(Role::Admin);
+        let (policy, quorum) = Policy::from_parts(policy, quorum).into_parts();
+        Self::put_setting(POLICY_KEY, (policy, quorum));
+        self.expire_cache();
+        events::emit(Event::AggregationPolicySet { policy, quorum });
+    }
+
+    /// Stops forwarding checks to the providers, e.g. when one of them is compromised. Checks then
+    /// refuse every account, or revert with `ApiError::User(310)` if `revert_checks` is set.
+    pub(crate) fn pause(&self, revert_checks: bool) {
+        self.assert_role(Role::Admin);
+        Self::put_setting(PAUSED_KEY, Some(revert_checks));
+        events::emit(Event::Paused { revert_checks });
+    }
+
+    pub(crate) fn unpause(&self) {
+        self.assert_role(Role::Admin);
+        if self.paused().is_some() {
+            Self::put_setting(PAUSED_KEY, Option::<bool>::None);
+            events::emit(Event::Unpaused);
+        }
+    }
+
+    /// Returns `None` while the proxy is running, otherwise whether paused checks revert.
+    pub(crate) fn paused(&self) -> Option<bool> {
+        Self::setting(PAUSED_KEY)
+    }
+
+    fn check_single(
+        provider_package_hash: ContractPackageHash,
+        version: Option<ContractVersion>,
+        entry_point: &str,
+        args: &RuntimeArgs,
+    ) -> bool {
+        call_versioned_contract(provider_package_hash, version, entry_point, args.clone())
+    }
+}
@@ -4,21 +4,34 @@
 use casper_contract::contract_api::runtime::{
     call_versioned_contract, get_caller, get_named_arg, revert,
 };
-use casper_types::{runtime_args, ApiError, Key, RuntimeArgs, U256};
+use casper_types::{runtime_args, ApiError, Key, RuntimeArgs, U256, U512};
 
+/// Calls `is_allowed` with the given `amount`, or `is_enabled` without one.
 #[no_mangle]
 pub extern "C" fn call() {
-    if get_named_arg::<bool>("result")
-        != call_versioned_contract::<bool>(
-            get_named_arg("synth_package_hash"),
+    let synth_package_hash = get_named_arg("synth_package_hash");
+    let answer = match get_named_arg::<Option<U512>>("amount") {
+        Some(amount) => call_versioned_contract::<bool>(
+            synth_package_hash,
+            None,
+            "is_allowed",
+            runtime_args! {
+                "account" => Key::Account(get_caller()),
+                "index" => Option::<U256>::None,
+                "amount" => amount
+            },
+        ),
+        None => call_versioned_contract::<bool>(
+            synth_package_hash,
             None,
             "is_enabled",
             runtime_args! {
                 "account" => Key::Account(get_caller()),
                 "index" => Option::<U256>::None
             },
-        )
-    {
+        ),
+    };
+    if get_named_arg::<bool>("result") != answer {
         revert(ApiError::User(999))
     }
 }
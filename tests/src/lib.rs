@@ -1,4 +1,3 @@
-use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use casper_engine_test_support::{
@@ -7,9 +6,20 @@ use casper_engine_test_support::{
 };
 use casper_execution_engine::storage::global_state::in_memory::InMemoryGlobalState;
 
+use casper_types::bytesrepr::{Bytes, FromBytes, ToBytes};
 use casper_types::system::mint;
 use casper_types::{account::AccountHash, runtime_args, PublicKey, RuntimeArgs, SecretKey, U512};
-use casper_types::{ContractHash, ContractPackageHash, Key};
+use casper_types::{
+    CLType, CLTyped, ContractHash, ContractPackageHash, Key, Parameter, URef, U256,
+};
+
+pub const ROLE_ADMIN: u8 = 0;
+pub const ROLE_OPERATOR: u8 = 1;
+pub const ROLE_PROVIDER_MANAGER: u8 = 2;
+
+pub const POLICY_ALL: u8 = 1;
+pub const POLICY_AT_LEAST: u8 = 2;
+pub const POLICY_WEIGHTED: u8 = 3;
 
 pub struct ProxyContract {
     pub builder: WasmTestBuilder<InMemoryGlobalState>,
@@ -22,6 +32,36 @@ pub struct ProxyContract {
 
 impl ProxyContract {
     pub fn deploy() -> Self {
+        Self::deploy_proxy(
+            "kyc-proxy.wasm",
+            runtime_args! {"name"=> "kyc", "initial_providers"=> Option::<Vec<ContractPackageHash>>::None},
+            "kyc-proxy",
+        )
+    }
+
+    /// Deploys the synth proxy, which forwards `is_enabled` and `is_allowed`.
+    pub fn deploy_synth_proxy() -> Self {
+        Self::deploy_proxy(
+            "synth-proxy.wasm",
+            runtime_args! {"name"=> "synth", "initial_providers"=> Option::<Vec<ContractPackageHash>>::None},
+            "synth-synth",
+        )
+    }
+
+    /// Deploys the forwarding proxy exposing the given checks.
+    pub fn deploy_forwarding(checks: Vec<(String, Vec<Parameter>)>) -> Self {
+        Self::deploy_proxy(
+            "forwarding-proxy.wasm",
+            runtime_args! {
+                "name" => "kyc",
+                "checks" => Bytes::from(checks.to_bytes().unwrap()),
+                "initial_providers" => Option::<Vec<ContractPackageHash>>::None
+            },
+            "kyc-forwarding",
+        )
+    }
+
+    fn deploy_proxy(code: &str, args: RuntimeArgs, named_keys_prefix: &str) -> Self {
         // We create 3 users. One to oversee and deploy the contract, one to send the payment
         // and one to receive it.
         let admin_public_key: PublicKey =
@@ -35,8 +75,7 @@ impl ProxyContract {
         let participant_two_account_addr = AccountHash::from(&participant_two_public_key);
         let participant_three_account_addr = AccountHash::from(&participant_three_public_key);
 
-        let code = PathBuf::from("kyc-proxy.wasm");
-        let args = runtime_args! {"name"=> "kyc", "initial_providers"=> Option::<Vec<ContractPackageHash>>::None};
+        let code = PathBuf::from(code);
         let deploy = DeployItemBuilder::new()
             .with_empty_payment_bytes(runtime_args! {ARG_AMOUNT => *DEFAULT_PAYMENT})
             .with_session_code(code, args)
@@ -115,7 +154,7 @@ impl ProxyContract {
             .query(
                 None,
                 Key::Account(admin_account_addr),
-                &["kyc-proxy_contract_hash".to_string()],
+                &[format!("{}_contract_hash", named_keys_prefix)],
             )
             .expect("should be stored value.")
             .as_cl_value()
@@ -128,8 +167,8 @@ impl ProxyContract {
                 None,
                 Key::Account(admin_account_addr),
                 &[
-                    "kyc-proxy_contract".to_string(),
-                    "kyc-proxy_contract_package".to_string(),
+                    format!("{}_contract", named_keys_prefix),
+                    format!("{}_contract_package", named_keys_prefix),
                 ],
             )
             .expect("should be stored value.")
@@ -156,21 +195,109 @@ impl ProxyContract {
         }
     }
 
+    /// Installs the kyc proxy as it was before the shared registry, with its own storage layout
+    /// and no providers. Upgrading it migrates the providers.
+    pub fn deploy_baseline() -> Self {
+        Self::deploy_proxy(
+            "kyc-proxy-baseline-test.wasm",
+            runtime_args! {"name" => "kyc", "initial_providers" => Option::<Vec<ContractPackageHash>>::None},
+            "kyc-proxy",
+        )
+    }
+
+    /// Installs the proxy code again under the same name, which adds a new version to the package.
+    pub fn upgrade(&mut self) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        let deploy = DeployItemBuilder::new()
+            .with_empty_payment_bytes(runtime_args! {ARG_AMOUNT => *DEFAULT_PAYMENT})
+            .with_session_code(
+                PathBuf::from("kyc-proxy.wasm"),
+                runtime_args! {
+                    "name" => "kyc",
+                    "initial_providers" => Option::<Vec<ContractPackageHash>>::None
+                },
+            )
+            .with_address(self.admin_account.1)
+            .with_authorization_keys(&[self.admin_account.1])
+            .build();
+        let execute_request = ExecuteRequestBuilder::from_deploy_item(deploy).build();
+        self.builder.exec(execute_request).commit().expect_success();
+        self.contract_hash = self
+            .builder
+            .query(
+                None,
+                Key::Account(self.admin_account.1),
+                &["kyc-proxy_contract_hash".to_string()],
+            )
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t()
+            .expect("should be contract hash.");
+        &mut self.builder
+    }
+
+    /// Installs another kyc proxy under `name` with the given initial providers and points the
+    /// harness at it.
+    pub fn install_with_providers(
+        &mut self,
+        name: &str,
+        initial_providers: Vec<ContractPackageHash>,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        let deploy = DeployItemBuilder::new()
+            .with_empty_payment_bytes(runtime_args! {ARG_AMOUNT => *DEFAULT_PAYMENT})
+            .with_session_code(
+                PathBuf::from("kyc-proxy.wasm"),
+                runtime_args! {
+                    "name" => name,
+                    "initial_providers" => Some(initial_providers)
+                },
+            )
+            .with_address(self.admin_account.1)
+            .with_authorization_keys(&[self.admin_account.1])
+            .build();
+        let execute_request = ExecuteRequestBuilder::from_deploy_item(deploy).build();
+        self.builder.exec(execute_request).commit().expect_success();
+        self.contract_hash = self
+            .builder
+            .query(
+                None,
+                Key::Account(self.admin_account.1),
+                &[format!("{}-proxy_contract_hash", name)],
+            )
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t()
+            .expect("should be contract hash.");
+        self.package_hash = self
+            .builder
+            .query(
+                None,
+                Key::Account(self.admin_account.1),
+                &[
+                    format!("{}-proxy_contract", name),
+                    format!("{}-proxy_contract_package", name),
+                ],
+            )
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t()
+            .expect("should be package hash.");
+        &mut self.builder
+    }
+
     pub fn deploy_kyc(
         &mut self,
         deployer: AccountHash,
         kyc_name: &str,
     ) -> (ContractPackageHash, ContractHash) {
-        let kyc_code = PathBuf::from("kyc-contract.wasm");
-        let mut meta = BTreeMap::new();
-        meta.insert("origin".to_string(), "kyc".to_string());
-
+        let kyc_code = PathBuf::from("mock-provider.wasm");
         let kyc_args = runtime_args! {
-            "name" => kyc_name,
-            "contract_name" => "kyc",
-            "symbol" => "symbol",
-            "meta" => meta,
-            "admin" => Key::Account(deployer)
+            "name" => kyc_name
         };
         let kyc_session = DeployItemBuilder::new()
             .with_empty_payment_bytes(runtime_args! {ARG_AMOUNT => *DEFAULT_PAYMENT})
@@ -185,7 +312,7 @@ impl ProxyContract {
                 .query(
                     None,
                     Key::Account(deployer),
-                    &["kyc_package_hash_wrapped".to_string()],
+                    &[format!("{}_package_hash_wrapped", kyc_name)],
                 )
                 .expect("should be stored value.")
                 .as_cl_value()
@@ -197,7 +324,7 @@ impl ProxyContract {
                 .query(
                     None,
                     Key::Account(deployer),
-                    &["kyc_contract_hash_wrapped".to_string()],
+                    &[format!("{}_contract_hash_wrapped", kyc_name)],
                 )
                 .expect("should be stored value.")
                 .as_cl_value()
@@ -208,22 +335,239 @@ impl ProxyContract {
         )
     }
 
-    pub fn add_kyc(&mut self, deployer: AccountHash, kyc_hash: [u8; 32], recipient: AccountHash) {
-        let mut token_meta = BTreeMap::new();
-        token_meta.insert("status".to_string(), "active".to_string());
-        let args = runtime_args! {
-            "recipient" => Key::Account(recipient),
-            "token_id" => Some(recipient.to_string()),
-            "token_meta" => token_meta
-        };
+    /// Deploys a synth proxy, a contract that does not implement `is_kyc_proved`.
+    pub fn deploy_synth(&mut self, deployer: AccountHash) -> ContractPackageHash {
         let deploy = DeployItemBuilder::new()
             .with_empty_payment_bytes(runtime_args! {ARG_AMOUNT => *DEFAULT_PAYMENT})
-            .with_stored_versioned_contract_by_hash(kyc_hash, None, "mint", args)
+            .with_session_code(
+                PathBuf::from("synth-proxy.wasm"),
+                runtime_args! {
+                    "name" => "synth",
+                    "initial_providers" => Option::<Vec<ContractPackageHash>>::None
+                },
+            )
             .with_address(deployer)
             .with_authorization_keys(&[deployer])
             .build();
         let execute_request = ExecuteRequestBuilder::from_deploy_item(deploy).build();
-        self.builder.exec(execute_request).commit();
+        self.builder.exec(execute_request).commit().expect_success();
+        self.builder
+            .query(
+                None,
+                Key::Account(deployer),
+                &[
+                    "synth-synth_contract".to_string(),
+                    "synth-synth_contract_package".to_string(),
+                ],
+            )
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t()
+            .expect("should be package hash.")
+    }
+
+    /// Makes the provider approve `recipient`.
+    pub fn add_kyc(&mut self, deployer: AccountHash, kyc_hash: [u8; 32], recipient: AccountHash) {
+        self.set_answer(deployer, kyc_hash, "is_kyc_proved", recipient, true);
+    }
+
+    /// Sets the answer of the provider to `check` for `account`.
+    pub fn set_answer(
+        &mut self,
+        deployer: AccountHash,
+        provider_hash: [u8; 32],
+        check: &str,
+        account: AccountHash,
+        answer: bool,
+    ) {
+        self.call_provider(
+            deployer,
+            provider_hash,
+            "set_answer",
+            runtime_args! {
+                "check" => check,
+                "account" => Key::Account(account),
+                "answer" => answer
+            },
+        );
+    }
+
+    /// Limits the amounts the provider allows `account` to move, `None` lifts the limit.
+    pub fn set_max_amount(
+        &mut self,
+        deployer: AccountHash,
+        provider_hash: [u8; 32],
+        account: AccountHash,
+        max_amount: Option<U512>,
+    ) {
+        self.call_provider(
+            deployer,
+            provider_hash,
+            "set_max_amount",
+            runtime_args! {
+                "account" => Key::Account(account),
+                "max_amount" => max_amount
+            },
+        );
+    }
+
+    /// Makes every check of the provider revert.
+    pub fn set_revert(&mut self, deployer: AccountHash, provider_hash: [u8; 32], revert: bool) {
+        self.call_provider(
+            deployer,
+            provider_hash,
+            "set_revert",
+            runtime_args! {"revert" => revert},
+        );
+    }
+
+    fn call_provider(
+        &mut self,
+        caller: AccountHash,
+        provider_hash: [u8; 32],
+        method: &str,
+        args: RuntimeArgs,
+    ) {
+        let deploy = DeployItemBuilder::new()
+            .with_empty_payment_bytes(runtime_args! {ARG_AMOUNT => *DEFAULT_PAYMENT})
+            .with_stored_session_hash(ContractHash::new(provider_hash), method, args)
+            .with_address(caller)
+            .with_authorization_keys(&[caller])
+            .build();
+        let execute_request = ExecuteRequestBuilder::from_deploy_item(deploy).build();
+        self.builder.exec(execute_request).commit().expect_success();
+    }
+
+    /// Reads an item of one of the proxy's dictionaries.
+    pub fn dictionary_item<T: FromBytes + CLTyped>(&self, dictionary: &str, item_key: &str) -> T {
+        let dictionary_uref = self
+            .builder
+            .get_contract(ContractHash::new(self.contract_hash))
+            .expect("should have contract.")
+            .named_keys()
+            .get(dictionary)
+            .expect("should have dictionary.")
+            .into_uref()
+            .expect("should be uref.");
+        self.builder
+            .query_dictionary_item(None, dictionary_uref, item_key)
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t()
+            .expect("should be of the expected type.")
+    }
+
+    /// Reads a value stored under one of the proxy's named keys.
+    pub fn named_value<T: FromBytes + CLTyped>(&self, name: &str) -> T {
+        self.builder
+            .query(None, Key::Hash(self.contract_hash), &[name.to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t()
+            .expect("should be of the expected type.")
+    }
+
+    /// Calls a read-only entry point of the proxy from a contract and returns its result.
+    pub fn query<T: FromBytes>(&mut self, entry_point: &str, args: RuntimeArgs) -> T {
+        self.query_in_block(entry_point, args, None)
+    }
+
+    pub fn query_at<T: FromBytes>(
+        &mut self,
+        entry_point: &str,
+        args: RuntimeArgs,
+        block_time: u64,
+    ) -> T {
+        self.query_in_block(entry_point, args, Some(block_time))
+    }
+
+    /// Calls a getter of the proxy, in a block with the given time if any.
+    fn query_in_block<T: FromBytes>(
+        &mut self,
+        entry_point: &str,
+        args: RuntimeArgs,
+        block_time: Option<u64>,
+    ) -> T {
+        let deploy = DeployItemBuilder::new()
+            .with_empty_payment_bytes(runtime_args! {ARG_AMOUNT => *DEFAULT_PAYMENT})
+            .with_session_code(
+                PathBuf::from("kyc-proxy-query-test.wasm"),
+                runtime_args! {
+                    "proxy_package_hash" => self.package_hash,
+                    "entry_point" => entry_point,
+                    "args" => Bytes::from(args.to_bytes().unwrap())
+                },
+            )
+            .with_address(self.admin_account.1)
+            .with_authorization_keys(&[self.admin_account.1])
+            .build();
+        let mut execute_request = ExecuteRequestBuilder::from_deploy_item(deploy);
+        if let Some(block_time) = block_time {
+            execute_request = execute_request.with_block_time(block_time);
+        }
+        self.builder
+            .exec(execute_request.build())
+            .commit()
+            .expect_success();
+        let result: Bytes = self
+            .builder
+            .query(
+                None,
+                Key::Account(self.admin_account.1),
+                &["query_result".to_string()],
+            )
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t()
+            .expect("should be bytes.");
+        let (value, remainder) = T::from_bytes(&result).expect("should be of the expected type.");
+        assert!(remainder.is_empty());
+        value
+    }
+
+    /// Returns the events emitted by the proxy so far, split into the event name and the
+    /// serialized fields.
+    pub fn events(&self) -> Vec<(String, Vec<u8>)> {
+        let events_length: u32 = self
+            .builder
+            .query(
+                None,
+                Key::Hash(self.contract_hash),
+                &["__events_length".to_string()],
+            )
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t()
+            .expect("should be u32.");
+        (0..events_length)
+            .map(|event_index| {
+                let event: Bytes = self.dictionary_item("__events", &event_index.to_string());
+                let (name, fields) = String::from_bytes(&event).expect("should have event name.");
+                (name, fields.to_vec())
+            })
+            .collect()
+    }
+
+    /// Returns the provider evaluated at the given position.
+    pub fn provider_at(&self, provider_index: u64) -> ContractPackageHash {
+        self.dictionary_item("kyc_provider_index", &format!("{:x}", provider_index))
+    }
+
+    fn main_purse(&self, account: AccountHash) -> URef {
+        self.builder
+            .get_account(account)
+            .expect("should get account")
+            .main_purse()
     }
 
     /// Getter function for the balance of an account.
@@ -245,7 +589,12 @@ impl ProxyContract {
     }
 
     /// Function that handles the creation and running of sessions.
-    fn call(&mut self, caller: AccountHash, method: &str, args: RuntimeArgs) {
+    fn call(
+        &mut self,
+        caller: AccountHash,
+        method: &str,
+        args: RuntimeArgs,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
         let deploy = DeployItemBuilder::new()
             .with_empty_payment_bytes(runtime_args! {ARG_AMOUNT => *DEFAULT_PAYMENT})
             .with_stored_versioned_contract_by_hash(self.contract_hash, None, method, args)
@@ -253,65 +602,512 @@ impl ProxyContract {
             .with_authorization_keys(&[caller])
             .build();
         let execute_request = ExecuteRequestBuilder::from_deploy_item(deploy).build();
-        self.builder.exec(execute_request).commit();
+        self.builder.exec(execute_request).commit()
     }
 
-    pub fn add_kyc_provider(&mut self, provider_package_hash_key: ContractPackageHash) {
+    /// Like `call`, but executed in a block with the given time.
+    fn call_at(
+        &mut self,
+        caller: AccountHash,
+        method: &str,
+        args: RuntimeArgs,
+        block_time: u64,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        let deploy = DeployItemBuilder::new()
+            .with_empty_payment_bytes(runtime_args! {ARG_AMOUNT => *DEFAULT_PAYMENT})
+            .with_stored_versioned_contract_by_hash(self.contract_hash, None, method, args)
+            .with_address(caller)
+            .with_authorization_keys(&[caller])
+            .build();
+        let execute_request = ExecuteRequestBuilder::from_deploy_item(deploy)
+            .with_block_time(block_time)
+            .build();
+        self.builder.exec(execute_request).commit()
+    }
+
+    /// Adds a provider to the forwarding proxy.
+    pub fn add_provider(&mut self, provider_package_hash_key: ContractPackageHash) {
         self.call(
             self.admin_account.1,
+            "add_provider",
+            runtime_args! {"provider"=>Key::Hash(provider_package_hash_key.value())},
+        )
+        .expect_success();
+    }
+
+    pub fn add_kyc_provider(&mut self, provider_package_hash_key: ContractPackageHash) {
+        self.add_kyc_provider_as(self.admin_account.1, provider_package_hash_key);
+    }
+
+    pub fn add_kyc_provider_as(
+        &mut self,
+        caller: AccountHash,
+        provider_package_hash_key: ContractPackageHash,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call(
+            caller,
             "add_kyc_provider",
             runtime_args! {"provider"=>Key::Hash(provider_package_hash_key.value())},
-        );
+        )
     }
 
-    pub fn ban_provider(&mut self, provider_package_hash_key: ContractPackageHash) {
+    pub fn add_synth_provider(&mut self, provider_package_hash_key: ContractPackageHash) {
+        self.add_synth_provider_as(self.admin_account.1, provider_package_hash_key)
+            .expect_success();
+    }
+
+    pub fn add_synth_provider_as(
+        &mut self,
+        caller: AccountHash,
+        provider_package_hash_key: ContractPackageHash,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call(
+            caller,
+            "add_synth_provider",
+            runtime_args! {"provider"=>Key::Hash(provider_package_hash_key.value())},
+        )
+    }
+
+    pub fn ban_synth_provider(&mut self, provider_package_hash_key: ContractPackageHash) {
         self.call(
             self.admin_account.1,
-            "ban_provider",
+            "ban_synth_provider",
             runtime_args! {"provider"=>Key::Hash(provider_package_hash_key.value())},
-        );
+        )
+        .expect_success();
     }
 
-    pub fn unban_provider(&mut self, provider_package_hash_key: ContractPackageHash) {
+    pub fn unban_synth_provider(&mut self, provider_package_hash_key: ContractPackageHash) {
         self.call(
             self.admin_account.1,
-            "unban_provider",
+            "unban_synth_provider",
+            runtime_args! {"provider"=>Key::Hash(provider_package_hash_key.value())},
+        )
+        .expect_success();
+    }
+
+    pub fn remove_kyc_provider(&mut self, provider_package_hash_key: ContractPackageHash) {
+        self.call(
+            self.admin_account.1,
+            "remove_kyc_provider",
             runtime_args! {"provider"=>Key::Hash(provider_package_hash_key.value())},
         );
     }
 
-    pub fn is_kyc_proved(&mut self, result: bool) -> &mut WasmTestBuilder<InMemoryGlobalState> {
-        let code = PathBuf::from("test_contract.wasm");
-        let deploy = DeployItemBuilder::new()
-            .with_empty_payment_bytes(runtime_args! {ARG_AMOUNT => *DEFAULT_PAYMENT})
-            .with_session_code(
-                code,
-                runtime_args! {
-                    "kyc_proxy_package_hash"=>self.package_hash,
-                    "result" => result
-                },
-            )
-            .with_address(self.admin_account.1)
-            .with_authorization_keys(&[self.admin_account.1])
-            .build();
-        let execute_request = ExecuteRequestBuilder::from_deploy_item(deploy).build();
-        self.builder.exec(execute_request).commit()
+    pub fn move_provider(
+        &mut self,
+        provider_package_hash_key: ContractPackageHash,
+        new_index: u64,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call(
+            self.admin_account.1,
+            "move_provider",
+            runtime_args! {
+                "provider" => Key::Hash(provider_package_hash_key.value()),
+                "new_index" => new_index
+            },
+        )
     }
-}
 
-#[test]
-fn test_deploy() {
-    ProxyContract::deploy();
-}
+    pub fn ban_provider(&mut self, provider_package_hash_key: ContractPackageHash) {
+        self.ban_provider_as(self.admin_account.1, provider_package_hash_key);
+    }
 
-#[test]
-fn test_no_provider() {
-    let mut proxy = ProxyContract::deploy();
-    proxy.is_kyc_proved(false);
-}
+    pub fn ban_provider_as(
+        &mut self,
+        caller: AccountHash,
+        provider_package_hash_key: ContractPackageHash,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call(
+            caller,
+            "ban_provider",
+            runtime_args! {"provider"=>Key::Hash(provider_package_hash_key.value())},
+        )
+    }
 
-#[test]
-#[should_panic = "User(999)"]
+    /// Bans the provider until `until`, in a block with the given time.
+    pub fn ban_provider_until_at(
+        &mut self,
+        caller: AccountHash,
+        provider_package_hash_key: ContractPackageHash,
+        until: u64,
+        block_time: u64,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call_at(
+            caller,
+            "ban_provider_until",
+            runtime_args! {
+                "provider" => Key::Hash(provider_package_hash_key.value()),
+                "until" => until
+            },
+            block_time,
+        )
+    }
+
+    pub fn unban_provider(&mut self, provider_package_hash_key: ContractPackageHash) {
+        self.unban_provider_as(self.admin_account.1, provider_package_hash_key);
+    }
+
+    pub fn unban_provider_as(
+        &mut self,
+        caller: AccountHash,
+        provider_package_hash_key: ContractPackageHash,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call(
+            caller,
+            "unban_provider",
+            runtime_args! {"provider"=>Key::Hash(provider_package_hash_key.value())},
+        )
+    }
+
+    pub fn set_provider_weight(
+        &mut self,
+        provider_package_hash_key: ContractPackageHash,
+        weight: u32,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call(
+            self.admin_account.1,
+            "set_provider_weight",
+            runtime_args! {
+                "provider" => Key::Hash(provider_package_hash_key.value()),
+                "weight" => weight
+            },
+        )
+    }
+
+    pub fn set_provider_version_as(
+        &mut self,
+        caller: AccountHash,
+        provider_package_hash_key: ContractPackageHash,
+        version: Option<u32>,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call(
+            caller,
+            "set_provider_version",
+            runtime_args! {
+                "provider" => Key::Hash(provider_package_hash_key.value()),
+                "version" => version
+            },
+        )
+    }
+
+    /// Calls one of the allowlist or denylist entry points for the given account.
+    pub fn override_account_as(
+        &mut self,
+        caller: AccountHash,
+        method: &str,
+        account: AccountHash,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call(
+            caller,
+            method,
+            runtime_args! {"account" => Key::Account(account)},
+        )
+    }
+
+    pub fn pause_as(
+        &mut self,
+        caller: AccountHash,
+        revert_checks: bool,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call(
+            caller,
+            "pause",
+            runtime_args! {"revert_checks" => revert_checks},
+        )
+    }
+
+    pub fn unpause(&mut self) {
+        self.call(self.admin_account.1, "unpause", runtime_args! {})
+            .expect_success();
+    }
+
+    pub fn set_cache_ttl(&mut self, ttl: Option<u64>) {
+        self.set_cache_ttl_as(self.admin_account.1, ttl)
+            .expect_success();
+    }
+
+    pub fn set_cache_ttl_as(
+        &mut self,
+        caller: AccountHash,
+        ttl: Option<u64>,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call(caller, "set_cache_ttl", runtime_args! {"ttl" => ttl})
+    }
+
+    /// Checks the admin account through `prove_and_cache` at the given block time.
+    pub fn prove_and_cache_at(
+        &mut self,
+        block_time: u64,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call_at(
+            self.admin_account.1,
+            "prove_and_cache",
+            runtime_args! {
+                "account" => Key::Account(self.admin_account.1),
+                "index" => Option::<U256>::None
+            },
+            block_time,
+        )
+    }
+
+    pub fn invalidate_cached_proof(&mut self) {
+        self.call(
+            self.admin_account.1,
+            "invalidate_cached_proof",
+            runtime_args! {
+                "account" => Key::Account(self.admin_account.1),
+                "index" => Option::<U256>::None
+            },
+        )
+        .expect_success();
+    }
+
+    pub fn flush_cache(&mut self) {
+        self.call(self.admin_account.1, "flush_cache", runtime_args! {})
+            .expect_success();
+    }
+
+    /// Returns whether the provider is banned, its ban expiry, and its weight and pinned version,
+    /// or `None` once it is removed.
+    pub fn provider_record(
+        &self,
+        provider_package_hash: ContractPackageHash,
+    ) -> Option<(bool, Option<u64>, (u32, Option<u32>))> {
+        self.dictionary_item("kyc_provider_records", &provider_package_hash.to_string())
+    }
+
+    /// Returns the weight and pinned version of the provider.
+    pub fn provider_settings(
+        &self,
+        provider_package_hash: ContractPackageHash,
+    ) -> (u32, Option<u32>) {
+        self.provider_record(provider_package_hash).unwrap().2
+    }
+
+    pub fn set_aggregation_policy(&mut self, policy: u8, quorum: u32) {
+        self.call(
+            self.admin_account.1,
+            "set_aggregation_policy",
+            runtime_args! {"policy" => policy, "quorum" => quorum},
+        )
+        .expect_success();
+    }
+
+    pub fn grant_role_as(
+        &mut self,
+        caller: AccountHash,
+        role: u8,
+        account: AccountHash,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call(
+            caller,
+            "grant_role",
+            runtime_args! {"role" => role, "account" => Key::Account(account)},
+        )
+    }
+
+    pub fn revoke_role_as(
+        &mut self,
+        caller: AccountHash,
+        role: u8,
+        account: AccountHash,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call(
+            caller,
+            "revoke_role",
+            runtime_args! {"role" => role, "account" => Key::Account(account)},
+        )
+    }
+
+    pub fn transfer_ownership_as(
+        &mut self,
+        caller: AccountHash,
+        new_admin: Key,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call(
+            caller,
+            "transfer_ownership",
+            runtime_args! {"new_admin" => new_admin},
+        )
+    }
+
+    pub fn accept_ownership_as(
+        &mut self,
+        caller: AccountHash,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call(caller, "accept_ownership", runtime_args! {})
+    }
+
+    /// Installs a contract that calls the proxy on behalf of its callers, e.g. to own the proxy,
+    /// and returns its package hash.
+    pub fn deploy_owner_contract(&mut self, deployer: AccountHash) -> ContractPackageHash {
+        let deploy = DeployItemBuilder::new()
+            .with_empty_payment_bytes(runtime_args! {ARG_AMOUNT => *DEFAULT_PAYMENT})
+            .with_session_code(PathBuf::from("kyc-proxy-owner-test.wasm"), runtime_args! {})
+            .with_address(deployer)
+            .with_authorization_keys(&[deployer])
+            .build();
+        let execute_request = ExecuteRequestBuilder::from_deploy_item(deploy).build();
+        self.builder.exec(execute_request).commit().expect_success();
+        self.builder
+            .query(
+                None,
+                Key::Account(deployer),
+                &["proxy_owner_package_hash_wrapped".to_string()],
+            )
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t()
+            .expect("should be package hash.")
+    }
+
+    /// Calls an entry point of the proxy through the owner contract.
+    pub fn call_through_owner(
+        &mut self,
+        caller: AccountHash,
+        owner_package_hash: ContractPackageHash,
+        method: &str,
+        args: RuntimeArgs,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        let deploy = DeployItemBuilder::new()
+            .with_empty_payment_bytes(runtime_args! {ARG_AMOUNT => *DEFAULT_PAYMENT})
+            .with_stored_versioned_contract_by_hash(
+                owner_package_hash.value(),
+                None,
+                "forward",
+                runtime_args! {
+                    "proxy_package_hash" => self.package_hash,
+                    "entry_point" => method,
+                    "args" => Bytes::from(args.to_bytes().unwrap())
+                },
+            )
+            .with_address(caller)
+            .with_authorization_keys(&[caller])
+            .build();
+        let execute_request = ExecuteRequestBuilder::from_deploy_item(deploy).build();
+        self.builder.exec(execute_request).commit()
+    }
+
+    pub fn cancel_ownership_transfer_as(
+        &mut self,
+        caller: AccountHash,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.call(caller, "cancel_ownership_transfer", runtime_args! {})
+    }
+
+    pub fn is_kyc_proved(&mut self, result: bool) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.check_kyc(result, None)
+    }
+
+    pub fn is_kyc_proved_at(
+        &mut self,
+        result: bool,
+        block_time: u64,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.check_kyc(result, Some(block_time))
+    }
+
+    /// Asks the kyc proxy about the admin account, in a block with the given time if any.
+    fn check_kyc(
+        &mut self,
+        result: bool,
+        block_time: Option<u64>,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        let code = PathBuf::from("kyc-proxy-test.wasm");
+        let deploy = DeployItemBuilder::new()
+            .with_empty_payment_bytes(runtime_args! {ARG_AMOUNT => *DEFAULT_PAYMENT})
+            .with_session_code(
+                code,
+                runtime_args! {
+                    "kyc_proxy_package_hash"=>self.package_hash,
+                    "result" => result
+                },
+            )
+            .with_address(self.admin_account.1)
+            .with_authorization_keys(&[self.admin_account.1])
+            .build();
+        let mut execute_request = ExecuteRequestBuilder::from_deploy_item(deploy);
+        if let Some(block_time) = block_time {
+            execute_request = execute_request.with_block_time(block_time);
+        }
+        self.builder.exec(execute_request.build()).commit()
+    }
+
+    pub fn is_enabled(&mut self, result: bool) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.check_synth(None, result)
+    }
+
+    pub fn is_allowed(
+        &mut self,
+        amount: U512,
+        result: bool,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        self.check_synth(Some(amount), result)
+    }
+
+    /// Asks the synth proxy about the admin account, through `is_allowed` if an amount is given
+    /// and `is_enabled` otherwise.
+    fn check_synth(
+        &mut self,
+        amount: Option<U512>,
+        result: bool,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        let code = PathBuf::from("synth-proxy-test.wasm");
+        let deploy = DeployItemBuilder::new()
+            .with_empty_payment_bytes(runtime_args! {ARG_AMOUNT => *DEFAULT_PAYMENT})
+            .with_session_code(
+                code,
+                runtime_args! {
+                    "synth_package_hash" => self.package_hash,
+                    "amount" => amount,
+                    "result" => result
+                },
+            )
+            .with_address(self.admin_account.1)
+            .with_authorization_keys(&[self.admin_account.1])
+            .build();
+        let execute_request = ExecuteRequestBuilder::from_deploy_item(deploy).build();
+        self.builder.exec(execute_request).commit()
+    }
+
+    pub fn is_kyc_proved_detailed(
+        &mut self,
+        result: Option<ContractPackageHash>,
+    ) -> &mut WasmTestBuilder<InMemoryGlobalState> {
+        let code = PathBuf::from("kyc-proxy-detailed-test.wasm");
+        let deploy = DeployItemBuilder::new()
+            .with_empty_payment_bytes(runtime_args! {ARG_AMOUNT => *DEFAULT_PAYMENT})
+            .with_session_code(
+                code,
+                runtime_args! {
+                    "kyc_proxy_package_hash"=>self.package_hash,
+                    "result" => result
+                },
+            )
+            .with_address(self.admin_account.1)
+            .with_authorization_keys(&[self.admin_account.1])
+            .build();
+        let execute_request = ExecuteRequestBuilder::from_deploy_item(deploy).build();
+        self.builder.exec(execute_request).commit()
+    }
+}
+
+#[test]
+fn test_deploy() {
+    ProxyContract::deploy();
+}
+
+#[test]
+fn test_no_provider() {
+    let mut proxy = ProxyContract::deploy();
+    proxy.is_kyc_proved(false);
+}
+
+#[test]
+#[should_panic = "User(999)"]
 fn test_no_provider_failing() {
     let mut proxy = ProxyContract::deploy();
     proxy.is_kyc_proved(true).expect_success();
@@ -421,3 +1217,1751 @@ fn test_unbanned_provider() {
     proxy.unban_provider(first_provider_package_hash);
     proxy.is_kyc_proved(true);
 }
+
+#[test]
+#[should_panic = "User(301)"]
+fn test_add_provider_not_admin() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy
+        .add_kyc_provider_as(proxy.participant_two.1, first_provider_package_hash)
+        .expect_success();
+}
+
+#[test]
+#[should_panic = "User(301)"]
+fn test_ban_provider_not_admin() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy
+        .ban_provider_as(proxy.participant_two.1, first_provider_package_hash)
+        .expect_success();
+}
+
+#[test]
+#[should_panic = "User(301)"]
+fn test_unban_provider_not_admin() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.ban_provider(first_provider_package_hash);
+    proxy
+        .unban_provider_as(proxy.participant_two.1, first_provider_package_hash)
+        .expect_success();
+}
+
+#[test]
+fn test_not_admin_cannot_add_provider() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy
+        .add_kyc_provider_as(proxy.participant_two.1, first_provider_package_hash)
+        .expect_failure();
+    proxy.is_kyc_proved(true).expect_failure();
+}
+
+#[test]
+fn test_operator_can_ban_provider() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy
+        .grant_role_as(
+            proxy.admin_account.1,
+            ROLE_OPERATOR,
+            proxy.participant_two.1,
+        )
+        .expect_success();
+    proxy
+        .ban_provider_as(proxy.participant_two.1, first_provider_package_hash)
+        .expect_success();
+    proxy
+        .unban_provider_as(proxy.participant_two.1, first_provider_package_hash)
+        .expect_success();
+}
+
+#[test]
+#[should_panic = "User(301)"]
+fn test_operator_cannot_add_provider() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy
+        .grant_role_as(
+            proxy.admin_account.1,
+            ROLE_OPERATOR,
+            proxy.participant_two.1,
+        )
+        .expect_success();
+    proxy
+        .add_kyc_provider_as(proxy.participant_two.1, first_provider_package_hash)
+        .expect_success();
+}
+
+#[test]
+fn test_provider_manager_can_add_provider() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy
+        .grant_role_as(
+            proxy.admin_account.1,
+            ROLE_PROVIDER_MANAGER,
+            proxy.participant_two.1,
+        )
+        .expect_success();
+    proxy
+        .add_kyc_provider_as(proxy.participant_two.1, first_provider_package_hash)
+        .expect_success();
+}
+
+#[test]
+#[should_panic = "User(301)"]
+fn test_revoked_operator_cannot_ban_provider() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy
+        .grant_role_as(
+            proxy.admin_account.1,
+            ROLE_OPERATOR,
+            proxy.participant_two.1,
+        )
+        .expect_success();
+    proxy
+        .revoke_role_as(
+            proxy.admin_account.1,
+            ROLE_OPERATOR,
+            proxy.participant_two.1,
+        )
+        .expect_success();
+    proxy
+        .ban_provider_as(proxy.participant_two.1, first_provider_package_hash)
+        .expect_success();
+}
+
+#[test]
+#[should_panic = "User(301)"]
+fn test_grant_role_not_admin() {
+    let mut proxy = ProxyContract::deploy();
+    proxy
+        .grant_role_as(proxy.participant_two.1, ROLE_ADMIN, proxy.participant_two.1)
+        .expect_success();
+}
+
+#[test]
+fn test_ownership_transfer() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy
+        .transfer_ownership_as(proxy.admin_account.1, Key::Account(proxy.participant_two.1))
+        .expect_success();
+    proxy
+        .accept_ownership_as(proxy.participant_two.1)
+        .expect_success();
+    proxy
+        .add_kyc_provider_as(proxy.participant_two.1, first_provider_package_hash)
+        .expect_success();
+    proxy
+        .ban_provider_as(proxy.admin_account.1, first_provider_package_hash)
+        .expect_failure();
+}
+
+#[test]
+fn test_ownership_transfer_to_contract() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let owner_package_hash = proxy.deploy_owner_contract(proxy.participant_two.1);
+    proxy
+        .transfer_ownership_as(proxy.admin_account.1, Key::Hash(owner_package_hash.value()))
+        .expect_success();
+    // the pending admin is the contract, not the account that installed it
+    proxy
+        .accept_ownership_as(proxy.participant_two.1)
+        .expect_failure();
+    proxy
+        .call_through_owner(
+            proxy.participant_two.1,
+            owner_package_hash,
+            "accept_ownership",
+            runtime_args! {},
+        )
+        .expect_success();
+    let admin: Key = proxy.named_value("admin");
+    assert_eq!(admin, Key::Hash(owner_package_hash.value()));
+
+    proxy
+        .call_through_owner(
+            proxy.participant_two.1,
+            owner_package_hash,
+            "add_kyc_provider",
+            runtime_args! {"provider" => Key::Hash(first_provider_package_hash.value())},
+        )
+        .expect_success();
+    proxy
+        .call_through_owner(
+            proxy.participant_two.1,
+            owner_package_hash,
+            "grant_role",
+            runtime_args! {
+                "role" => ROLE_OPERATOR,
+                "account" => Key::Account(proxy.participant_three.1)
+            },
+        )
+        .expect_success();
+    proxy
+        .ban_provider_as(proxy.participant_three.1, first_provider_package_hash)
+        .expect_success();
+    let active: Option<bool> = proxy.query(
+        "is_provider_active",
+        runtime_args! {"provider" => Key::Hash(first_provider_package_hash.value())},
+    );
+    assert_eq!(active, Some(false));
+    // the previous admin lost its rights
+    proxy
+        .unban_provider_as(proxy.admin_account.1, first_provider_package_hash)
+        .expect_failure();
+}
+
+#[test]
+#[should_panic = "User(304)"]
+fn test_ownership_accept_by_other_account() {
+    let mut proxy = ProxyContract::deploy();
+    proxy
+        .transfer_ownership_as(proxy.admin_account.1, Key::Account(proxy.participant_two.1))
+        .expect_success();
+    proxy
+        .accept_ownership_as(proxy.participant_three.1)
+        .expect_success();
+}
+
+#[test]
+#[should_panic = "User(304)"]
+fn test_ownership_transfer_cancelled() {
+    let mut proxy = ProxyContract::deploy();
+    proxy
+        .transfer_ownership_as(proxy.admin_account.1, Key::Account(proxy.participant_two.1))
+        .expect_success();
+    proxy
+        .cancel_ownership_transfer_as(proxy.admin_account.1)
+        .expect_success();
+    proxy
+        .accept_ownership_as(proxy.participant_two.1)
+        .expect_success();
+}
+
+#[test]
+#[should_panic = "User(301)"]
+fn test_ownership_transfer_not_admin() {
+    let mut proxy = ProxyContract::deploy();
+    proxy
+        .transfer_ownership_as(
+            proxy.participant_two.1,
+            Key::Account(proxy.participant_two.1),
+        )
+        .expect_success();
+}
+
+#[test]
+#[should_panic = "User(305)"]
+fn test_init_twice() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy
+        .call(
+            proxy.admin_account.1,
+            "init",
+            runtime_args! {"initial_providers" => vec![first_provider_package_hash]},
+        )
+        .expect_success();
+}
+
+#[test]
+#[should_panic = "User(305)"]
+fn test_init_not_admin() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy
+        .call(
+            proxy.participant_two.1,
+            "init",
+            runtime_args! {"initial_providers" => vec![first_provider_package_hash]},
+        )
+        .expect_success();
+}
+
+#[test]
+fn test_duplicate_initial_providers() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "second");
+    proxy.install_with_providers(
+        "listed",
+        vec![
+            first_provider_package_hash,
+            second_provider_package_hash,
+            first_provider_package_hash,
+        ],
+    );
+    let provider_count: u64 = proxy.named_value("provider_count");
+    assert_eq!(provider_count, 2);
+    let active_provider_count: u64 = proxy.named_value("active_provider_count");
+    assert_eq!(active_provider_count, 2);
+    assert_eq!(proxy.provider_at(0), first_provider_package_hash);
+    assert_eq!(proxy.provider_at(1), second_provider_package_hash);
+
+    // listed once, the first provider cannot make up a quorum of two on its own
+    proxy.set_aggregation_policy(POLICY_AT_LEAST, 2);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.is_kyc_proved(false).expect_success();
+    proxy.add_kyc(
+        proxy.participant_three.1,
+        second_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.is_kyc_proved(true).expect_success();
+}
+
+#[test]
+fn test_removed_provider() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.remove_kyc_provider(first_provider_package_hash);
+    proxy.is_kyc_proved(false).expect_success();
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.is_kyc_proved(true).expect_success();
+}
+
+#[test]
+fn test_removed_provider_compaction() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, _second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "second");
+    let (third_provider_package_hash, third_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "third");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc_provider(second_provider_package_hash);
+    proxy.add_kyc_provider(third_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_three.1,
+        third_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    // the following providers move up a slot and are still asked
+    proxy.remove_kyc_provider(first_provider_package_hash);
+    proxy.is_kyc_proved(true).expect_success();
+    proxy.remove_kyc_provider(third_provider_package_hash);
+    proxy.is_kyc_proved(false).expect_success();
+}
+
+#[test]
+fn test_remove_provider_keeps_order() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, _second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "second");
+    let (third_provider_package_hash, _third_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "third");
+    let (fourth_provider_package_hash, _fourth_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "fourth");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc_provider(second_provider_package_hash);
+    proxy.add_kyc_provider(third_provider_package_hash);
+    proxy.add_kyc_provider(fourth_provider_package_hash);
+    proxy.ban_provider(third_provider_package_hash);
+    proxy
+        .set_provider_weight(fourth_provider_package_hash, 3)
+        .expect_success();
+
+    proxy.remove_kyc_provider(second_provider_package_hash);
+    let provider_count: u64 = proxy.named_value("provider_count");
+    assert_eq!(provider_count, 3);
+    assert_eq!(proxy.provider_at(0), first_provider_package_hash);
+    assert_eq!(proxy.provider_at(1), third_provider_package_hash);
+    assert_eq!(proxy.provider_at(2), fourth_provider_package_hash);
+    let active_provider_count: u64 = proxy.named_value("active_provider_count");
+    assert_eq!(active_provider_count, 2);
+    // the active list carries the weight and pinned version, so checks skip the records
+    let active: Vec<(ContractPackageHash, Option<u64>, (u32, Option<u32>))> = (0
+        ..active_provider_count)
+        .map(|index| proxy.dictionary_item("kyc_active_providers", &format!("{:x}", index)))
+        .collect();
+    assert_eq!(
+        active,
+        vec![
+            (first_provider_package_hash, None, (1, None)),
+            (fourth_provider_package_hash, None, (3, None))
+        ]
+    );
+}
+
+#[test]
+fn test_provider_queries() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, _second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "second");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc_provider(second_provider_package_hash);
+    proxy.ban_provider(first_provider_package_hash);
+    proxy
+        .set_provider_weight(second_provider_package_hash, 3)
+        .expect_success();
+    proxy
+        .set_provider_version_as(proxy.admin_account.1, second_provider_package_hash, Some(1))
+        .expect_success();
+
+    let providers: Vec<(ContractPackageHash, bool)> = proxy.query(
+        "get_providers",
+        runtime_args! {"offset" => 0u64, "limit" => 10u64},
+    );
+    assert_eq!(
+        providers,
+        vec![
+            (first_provider_package_hash, false),
+            (second_provider_package_hash, true)
+        ]
+    );
+    let providers: Vec<(ContractPackageHash, bool)> = proxy.query(
+        "get_providers",
+        runtime_args! {"offset" => 1u64, "limit" => 10u64},
+    );
+    assert_eq!(providers, vec![(second_provider_package_hash, true)]);
+    let provider_count: u64 = proxy.query("get_provider_count", runtime_args! {});
+    assert_eq!(provider_count, 2);
+    let active: Option<bool> = proxy.query(
+        "is_provider_active",
+        runtime_args! {"provider" => Key::Hash(first_provider_package_hash.value())},
+    );
+    assert_eq!(active, Some(false));
+    let active: Option<bool> = proxy.query(
+        "is_provider_active",
+        runtime_args! {"provider" => Key::Hash(second_provider_package_hash.value())},
+    );
+    assert_eq!(active, Some(true));
+    let active: Option<bool> = proxy.query(
+        "is_provider_active",
+        runtime_args! {"provider" => Key::Hash([7u8; 32])},
+    );
+    assert_eq!(active, None);
+    let version: Option<u32> = proxy.query(
+        "get_provider_version",
+        runtime_args! {"provider" => Key::Hash(second_provider_package_hash.value())},
+    );
+    assert_eq!(version, Some(1));
+    let (weight, _) = proxy.provider_settings(second_provider_package_hash);
+    assert_eq!(weight, 3);
+}
+
+#[test]
+fn test_add_non_conforming_provider() {
+    let mut proxy = ProxyContract::deploy();
+    let synth_package_hash = proxy.deploy_synth(proxy.participant_two.1);
+    proxy
+        .add_kyc_provider_as(proxy.admin_account.1, synth_package_hash)
+        .expect_failure();
+    let provider_count: u64 = proxy.named_value("provider_count");
+    assert_eq!(provider_count, 0);
+}
+
+#[test]
+fn test_policy_all_requires_every_provider() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "second");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc_provider(second_provider_package_hash);
+    proxy.set_aggregation_policy(POLICY_ALL, 0);
+
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.is_kyc_proved(false).expect_success();
+    proxy.add_kyc(
+        proxy.participant_three.1,
+        second_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.is_kyc_proved(true).expect_success();
+}
+
+#[test]
+fn test_policy_all_ignores_banned_provider() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, _second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "second");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc_provider(second_provider_package_hash);
+    proxy.set_aggregation_policy(POLICY_ALL, 0);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.ban_provider(second_provider_package_hash);
+    proxy.is_kyc_proved(true).expect_success();
+}
+
+#[test]
+fn test_policy_all_no_provider() {
+    let mut proxy = ProxyContract::deploy();
+    proxy.set_aggregation_policy(POLICY_ALL, 0);
+    proxy.is_kyc_proved(false).expect_success();
+}
+
+#[test]
+fn test_policy_at_least_quorum() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "second");
+    let (third_provider_package_hash, _third_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "third");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc_provider(second_provider_package_hash);
+    proxy.add_kyc_provider(third_provider_package_hash);
+    proxy.set_aggregation_policy(POLICY_AT_LEAST, 2);
+
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.is_kyc_proved(false).expect_success();
+    proxy.add_kyc(
+        proxy.participant_three.1,
+        second_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.is_kyc_proved(true).expect_success();
+}
+
+#[test]
+#[should_panic = "User(306)"]
+fn test_policy_at_least_zero() {
+    let mut proxy = ProxyContract::deploy();
+    proxy.set_aggregation_policy(POLICY_AT_LEAST, 0);
+}
+
+#[test]
+fn test_policy_weighted_threshold() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "second");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc_provider(second_provider_package_hash);
+    proxy
+        .set_provider_weight(first_provider_package_hash, 2)
+        .expect_success();
+    proxy.set_aggregation_policy(POLICY_WEIGHTED, 3);
+
+    proxy.add_kyc(
+        proxy.participant_three.1,
+        second_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.is_kyc_proved(false).expect_success();
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.is_kyc_proved(true).expect_success();
+}
+
+#[test]
+fn test_policy_weighted_single_trusted_provider() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy
+        .set_provider_weight(first_provider_package_hash, 5)
+        .expect_success();
+    proxy.set_aggregation_policy(POLICY_WEIGHTED, 5);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.is_kyc_proved(true).expect_success();
+}
+
+#[test]
+#[should_panic = "User(307)"]
+fn test_weight_unknown_provider() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy
+        .set_provider_weight(first_provider_package_hash, 2)
+        .expect_success();
+}
+
+#[test]
+fn test_move_provider() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, _second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "second");
+    let (third_provider_package_hash, _third_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "third");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc_provider(second_provider_package_hash);
+    proxy.add_kyc_provider(third_provider_package_hash);
+
+    proxy
+        .move_provider(third_provider_package_hash, 0)
+        .expect_success();
+    assert_eq!(proxy.provider_at(0), third_provider_package_hash);
+    assert_eq!(proxy.provider_at(1), first_provider_package_hash);
+    assert_eq!(proxy.provider_at(2), second_provider_package_hash);
+
+    proxy
+        .move_provider(third_provider_package_hash, 2)
+        .expect_success();
+    assert_eq!(proxy.provider_at(0), first_provider_package_hash);
+    assert_eq!(proxy.provider_at(1), second_provider_package_hash);
+    assert_eq!(proxy.provider_at(2), third_provider_package_hash);
+}
+
+#[test]
+#[should_panic = "User(308)"]
+fn test_move_provider_out_of_bounds() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy
+        .move_provider(first_provider_package_hash, 1)
+        .expect_success();
+}
+
+#[test]
+fn test_pinned_provider_version() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy
+        .set_provider_version_as(proxy.admin_account.1, first_provider_package_hash, Some(1))
+        .expect_success();
+    proxy.is_kyc_proved(true).expect_success();
+    assert_eq!(
+        proxy.provider_settings(first_provider_package_hash),
+        (1, Some(1))
+    );
+
+    // the provider has no second version, so the pinned call fails until the pin is cleared
+    proxy
+        .set_provider_version_as(proxy.admin_account.1, first_provider_package_hash, Some(2))
+        .expect_success();
+    proxy.is_kyc_proved(true).expect_failure();
+    proxy
+        .set_provider_version_as(proxy.admin_account.1, first_provider_package_hash, None)
+        .expect_success();
+    proxy.is_kyc_proved(true).expect_success();
+}
+
+#[test]
+#[should_panic = "User(301)"]
+fn test_pin_provider_version_not_admin() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy
+        .set_provider_version_as(
+            proxy.participant_two.1,
+            first_provider_package_hash,
+            Some(1),
+        )
+        .expect_success();
+}
+
+#[test]
+fn test_upgrade_keeps_providers() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    let old_contract_hash = proxy.contract_hash;
+
+    proxy.upgrade();
+    assert_ne!(proxy.contract_hash, old_contract_hash);
+    assert_eq!(proxy.provider_at(0), first_provider_package_hash);
+    let schema_version: u32 = proxy
+        .builder
+        .query(
+            None,
+            Key::Hash(proxy.contract_hash),
+            &["schema_version".to_string()],
+        )
+        .expect("should be stored value.")
+        .as_cl_value()
+        .expect("should be cl value.")
+        .clone()
+        .into_t()
+        .expect("should be u32.");
+    assert_eq!(schema_version, 1);
+    proxy.is_kyc_proved(true).expect_success();
+}
+
+#[test]
+fn test_upgrade_after_ownership_transfer() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy
+        .transfer_ownership_as(proxy.admin_account.1, Key::Account(proxy.participant_two.1))
+        .expect_success();
+    proxy
+        .accept_ownership_as(proxy.participant_two.1)
+        .expect_success();
+    let old_contract_hash = proxy.contract_hash;
+
+    // the installing account still holds the access token of the package
+    proxy.upgrade();
+    assert_ne!(proxy.contract_hash, old_contract_hash);
+    let admin: Key = proxy.named_value("admin");
+    assert_eq!(admin, Key::Account(proxy.participant_two.1));
+    proxy.is_kyc_proved(true).expect_success();
+    proxy
+        .ban_provider_as(proxy.admin_account.1, first_provider_package_hash)
+        .expect_failure();
+}
+
+#[test]
+fn test_upgrade_from_baseline() {
+    let mut proxy = ProxyContract::deploy_baseline();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, _second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "second");
+    let (third_provider_package_hash, third_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "third");
+    // the baseline code has the same entry points for these
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc_provider(second_provider_package_hash);
+    proxy.add_kyc_provider(third_provider_package_hash);
+    proxy.ban_provider(second_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_three.1,
+        third_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.is_kyc_proved(true).expect_success();
+    let old_contract_hash = proxy.contract_hash;
+
+    proxy.upgrade();
+    assert_ne!(proxy.contract_hash, old_contract_hash);
+    let schema_version: u32 = proxy.named_value("schema_version");
+    assert_eq!(schema_version, 1);
+    let provider_count: u64 = proxy.named_value("provider_count");
+    assert_eq!(provider_count, 3);
+    assert_eq!(proxy.provider_at(0), first_provider_package_hash);
+    assert_eq!(proxy.provider_at(1), second_provider_package_hash);
+    assert_eq!(proxy.provider_at(2), third_provider_package_hash);
+    assert_eq!(
+        proxy.provider_record(first_provider_package_hash),
+        Some((false, None, (1, None)))
+    );
+    assert_eq!(
+        proxy.provider_record(second_provider_package_hash),
+        Some((true, None, (1, None)))
+    );
+    assert_eq!(
+        proxy.provider_record(third_provider_package_hash),
+        Some((false, None, (1, None)))
+    );
+    let active_provider_count: u64 = proxy.named_value("active_provider_count");
+    assert_eq!(active_provider_count, 2);
+    // the baseline had no admin, the upgrading account becomes the admin
+    let admin: Key = proxy.named_value("admin");
+    assert_eq!(admin, Key::Account(proxy.admin_account.1));
+
+    proxy.is_kyc_proved(true).expect_success();
+    proxy.unban_provider(second_provider_package_hash);
+    assert_eq!(
+        proxy.provider_record(second_provider_package_hash),
+        Some((false, None, (1, None)))
+    );
+
+    // upgrading again keeps the migrated registry
+    proxy.upgrade();
+    assert_eq!(proxy.provider_at(2), third_provider_package_hash);
+    proxy.is_kyc_proved(true).expect_success();
+}
+
+#[test]
+#[should_panic = "User(301)"]
+fn test_migrate_baseline_upgrade_without_access_token() {
+    let mut proxy = ProxyContract::deploy_baseline();
+    proxy.upgrade();
+    // the migration stored the access token of the installer, later migrations require it
+    let purse = proxy.main_purse(proxy.participant_two.1);
+    proxy
+        .call(
+            proxy.participant_two.1,
+            "migrate",
+            runtime_args! {"access_token" => purse},
+        )
+        .expect_success();
+}
+
+/// Asks the kyc proxy once with only the first of three providers approving and once with only
+/// the third, and returns the gas each refusing provider asked before the approving one adds.
+fn refusing_provider_gas_cost(
+    proxy: &mut ProxyContract,
+    first_provider_hash: ContractHash,
+    third_provider_hash: ContractHash,
+) -> U512 {
+    proxy.set_answer(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        "is_kyc_proved",
+        proxy.admin_account.1,
+        true,
+    );
+    proxy.set_answer(
+        proxy.participant_three.1,
+        third_provider_hash.value(),
+        "is_kyc_proved",
+        proxy.admin_account.1,
+        false,
+    );
+    proxy.is_kyc_proved(true).expect_success();
+    let one_provider_asked_cost = proxy.builder.last_exec_gas_cost().value();
+
+    proxy.set_answer(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        "is_kyc_proved",
+        proxy.admin_account.1,
+        false,
+    );
+    proxy.set_answer(
+        proxy.participant_three.1,
+        third_provider_hash.value(),
+        "is_kyc_proved",
+        proxy.admin_account.1,
+        true,
+    );
+    proxy.is_kyc_proved(true).expect_success();
+    let three_providers_asked_cost = proxy.builder.last_exec_gas_cost().value();
+
+    (three_providers_asked_cost - one_provider_asked_cost) / 2
+}
+
+/// A check reads one item of the active list per asked provider, where the baseline code read
+/// the position and the status of every provider, so each refusing provider costs less once the
+/// baseline proxy is upgraded.
+#[test]
+fn test_check_gas_cost() {
+    let mut proxy = ProxyContract::deploy_baseline();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, _second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "second");
+    let (third_provider_package_hash, third_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "third");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc_provider(second_provider_package_hash);
+    proxy.add_kyc_provider(third_provider_package_hash);
+    let baseline_cost =
+        refusing_provider_gas_cost(&mut proxy, first_provider_hash, third_provider_hash);
+
+    proxy.upgrade();
+    let upgraded_cost =
+        refusing_provider_gas_cost(&mut proxy, first_provider_hash, third_provider_hash);
+
+    assert!(upgraded_cost < baseline_cost);
+}
+
+/// Banned providers are left out of the active list, so asking the only active provider costs the
+/// same however many banned providers come before it.
+#[test]
+fn test_check_gas_cost_ignores_banned_providers() {
+    let mut proxy = ProxyContract::deploy();
+    let (approving_provider_package_hash, approving_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "approving");
+    proxy.add_kyc_provider(approving_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        approving_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.is_kyc_proved(true).expect_success();
+    let without_banned_cost = proxy.builder.last_exec_gas_cost();
+
+    for name in &["first", "second", "third", "fourth", "fifth"] {
+        let (provider_package_hash, _provider_hash) =
+            proxy.deploy_kyc(proxy.participant_two.1, name);
+        proxy.add_kyc_provider(provider_package_hash);
+        proxy.ban_provider(provider_package_hash);
+    }
+    proxy
+        .move_provider(approving_provider_package_hash, 5)
+        .expect_success();
+    assert_eq!(proxy.provider_at(5), approving_provider_package_hash);
+    proxy.is_kyc_proved(true).expect_success();
+    let with_banned_cost = proxy.builder.last_exec_gas_cost();
+
+    assert_eq!(with_banned_cost, without_banned_cost);
+}
+
+#[test]
+#[should_panic = "User(301)"]
+fn test_migrate_without_access_token() {
+    let mut proxy = ProxyContract::deploy();
+    proxy
+        .transfer_ownership_as(proxy.admin_account.1, Key::Account(proxy.participant_two.1))
+        .expect_success();
+    proxy
+        .accept_ownership_as(proxy.participant_two.1)
+        .expect_success();
+    // not even the admin can migrate without the access token
+    let purse = proxy.main_purse(proxy.participant_two.1);
+    proxy
+        .call(
+            proxy.participant_two.1,
+            "migrate",
+            runtime_args! {"access_token" => purse},
+        )
+        .expect_success();
+}
+
+#[test]
+fn test_paused_proxy_refuses() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy
+        .pause_as(proxy.admin_account.1, false)
+        .expect_success();
+    proxy.is_kyc_proved(false).expect_success();
+    proxy.is_kyc_proved_detailed(None).expect_success();
+
+    proxy.unpause();
+    proxy.is_kyc_proved(true).expect_success();
+}
+
+#[test]
+#[should_panic = "User(310)"]
+fn test_paused_proxy_reverts() {
+    let mut proxy = ProxyContract::deploy();
+    proxy.pause_as(proxy.admin_account.1, true).expect_success();
+    proxy.is_kyc_proved(false).expect_success();
+}
+
+#[test]
+#[should_panic = "User(301)"]
+fn test_pause_not_admin() {
+    let mut proxy = ProxyContract::deploy();
+    proxy
+        .pause_as(proxy.participant_two.1, false)
+        .expect_success();
+}
+
+#[test]
+fn test_denied_account() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    let admin = proxy.admin_account.1;
+    proxy
+        .override_account_as(admin, "deny_account", admin)
+        .expect_success();
+    proxy.is_kyc_proved(false).expect_success();
+    let denied: Option<bool> = proxy.dictionary_item(
+        "kyc_account_overrides",
+        &ContractPackageHash::new(admin.value()).to_string(),
+    );
+    assert_eq!(denied, Some(false));
+
+    // removing the account from the allowlist leaves the denylist untouched
+    proxy
+        .override_account_as(admin, "remove_allowed_account", admin)
+        .expect_success();
+    proxy.is_kyc_proved(false).expect_success();
+    proxy
+        .override_account_as(admin, "remove_denied_account", admin)
+        .expect_success();
+    proxy.is_kyc_proved(true).expect_success();
+}
+
+#[test]
+fn test_allowed_account() {
+    let mut proxy = ProxyContract::deploy();
+    let admin = proxy.admin_account.1;
+    proxy
+        .override_account_as(admin, "allow_account", admin)
+        .expect_success();
+    proxy.is_kyc_proved(true).expect_success();
+    let proxy_package_hash = proxy.package_hash;
+    proxy
+        .is_kyc_proved_detailed(Some(proxy_package_hash))
+        .expect_success();
+
+    proxy
+        .override_account_as(admin, "remove_allowed_account", admin)
+        .expect_success();
+    proxy.is_kyc_proved(false).expect_success();
+}
+
+#[test]
+#[should_panic = "User(301)"]
+fn test_deny_account_not_admin() {
+    let mut proxy = ProxyContract::deploy();
+    let admin = proxy.admin_account.1;
+    proxy
+        .override_account_as(proxy.participant_two.1, "deny_account", admin)
+        .expect_success();
+}
+
+#[test]
+fn test_detailed_reports_approving_provider() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "second");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc_provider(second_provider_package_hash);
+    proxy.is_kyc_proved_detailed(None).expect_success();
+
+    proxy.add_kyc(
+        proxy.participant_three.1,
+        second_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy
+        .is_kyc_proved_detailed(Some(second_provider_package_hash))
+        .expect_success();
+}
+
+#[test]
+fn test_registry_events() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.ban_provider(first_provider_package_hash);
+    proxy.unban_provider(first_provider_package_hash);
+    proxy
+        .grant_role_as(
+            proxy.admin_account.1,
+            ROLE_OPERATOR,
+            proxy.participant_two.1,
+        )
+        .expect_success();
+
+    let provider_bytes = first_provider_package_hash.to_bytes().unwrap();
+    let mut role_bytes = ROLE_OPERATOR.to_bytes().unwrap();
+    role_bytes.append(&mut Key::Account(proxy.participant_two.1).to_bytes().unwrap());
+    assert_eq!(
+        proxy.events(),
+        vec![
+            ("event_ProviderAdded".to_string(), provider_bytes.clone()),
+            ("event_ProviderBanned".to_string(), provider_bytes.clone()),
+            ("event_ProviderUnbanned".to_string(), provider_bytes),
+            ("event_RoleGranted".to_string(), role_bytes),
+        ]
+    );
+}
+
+#[test]
+fn test_ownership_events() {
+    let mut proxy = ProxyContract::deploy();
+    let admin = Key::Account(proxy.admin_account.1);
+    let new_admin = Key::Account(proxy.participant_two.1);
+    proxy
+        .transfer_ownership_as(proxy.admin_account.1, new_admin)
+        .expect_success();
+    proxy
+        .accept_ownership_as(proxy.participant_two.1)
+        .expect_success();
+
+    let mut transfer_bytes = admin.to_bytes().unwrap();
+    transfer_bytes.append(&mut new_admin.to_bytes().unwrap());
+    assert_eq!(
+        proxy.events(),
+        vec![
+            (
+                "event_OwnershipTransferStarted".to_string(),
+                transfer_bytes.clone()
+            ),
+            ("event_OwnershipTransferred".to_string(), transfer_bytes),
+        ]
+    );
+}
+
+fn is_kyc_proved_parameters() -> Vec<Parameter> {
+    vec![
+        Parameter::new("account", Key::cl_type()),
+        Parameter::new("index", CLType::Option(Box::new(U256::cl_type()))),
+    ]
+}
+
+#[test]
+fn test_forwarding_proxy() {
+    let mut proxy = ProxyContract::deploy_forwarding(vec![(
+        "is_kyc_proved".to_string(),
+        is_kyc_proved_parameters(),
+    )]);
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_provider(first_provider_package_hash);
+    proxy.is_kyc_proved(false).expect_success();
+
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.is_kyc_proved(true).expect_success();
+    proxy
+        .is_kyc_proved_detailed(Some(first_provider_package_hash))
+        .expect_success();
+}
+
+#[test]
+#[should_panic = "User(309)"]
+fn test_forwarding_proxy_unknown_check() {
+    ProxyContract::deploy_forwarding(vec![("is_unknown".to_string(), is_kyc_proved_parameters())]);
+}
+
+#[test]
+#[should_panic = "User(315)"]
+fn test_forwarding_proxy_uref_parameter() {
+    ProxyContract::deploy_forwarding(vec![(
+        "is_accredited".to_string(),
+        vec![Parameter::new("purse", CLType::URef)],
+    )]);
+}
+
+#[test]
+fn test_forwarding_proxy_probes_provider() {
+    let mut proxy = ProxyContract::deploy_forwarding(vec![
+        ("is_kyc_proved".to_string(), is_kyc_proved_parameters()),
+        ("is_accredited".to_string(), is_kyc_proved_parameters()),
+    ]);
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    // the mock provider does not implement `is_accredited`
+    proxy
+        .call(
+            proxy.admin_account.1,
+            "add_provider",
+            runtime_args! {"provider" => Key::Hash(first_provider_package_hash.value())},
+        )
+        .expect_failure();
+    let provider_count: u64 = proxy.named_value("provider_count");
+    assert_eq!(provider_count, 0);
+}
+
+#[test]
+#[should_panic = "EarlyEndOfStream"]
+fn test_forwarding_proxy_malformed_arg() {
+    let mut proxy = ProxyContract::deploy_forwarding(vec![(
+        "is_kyc_proved".to_string(),
+        is_kyc_proved_parameters(),
+    )]);
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_provider(first_provider_package_hash);
+    proxy
+        .call(
+            proxy.admin_account.1,
+            "is_kyc_proved",
+            runtime_args! {"account" => 0u8, "index" => Option::<U256>::None},
+        )
+        .expect_success();
+}
+
+#[test]
+#[should_panic = "LeftOverBytes"]
+fn test_forwarding_proxy_arg_with_left_over_bytes() {
+    let mut proxy = ProxyContract::deploy_forwarding(vec![(
+        "is_kyc_proved".to_string(),
+        is_kyc_proved_parameters(),
+    )]);
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_provider(first_provider_package_hash);
+    proxy
+        .call(
+            proxy.admin_account.1,
+            "is_kyc_proved",
+            runtime_args! {
+                "account" => (Key::Account(proxy.admin_account.1), 0u8),
+                "index" => Option::<U256>::None
+            },
+        )
+        .expect_success();
+}
+
+#[test]
+fn test_reverting_provider_banned() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "second");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc_provider(second_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_three.1,
+        second_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+
+    // the revert of the first provider fails the whole check until an operator bans it
+    proxy.set_revert(proxy.participant_two.1, first_provider_hash.value(), true);
+    proxy.is_kyc_proved(true).expect_failure();
+    proxy.ban_provider(first_provider_package_hash);
+    proxy.is_kyc_proved(true).expect_success();
+}
+
+#[test]
+fn test_cached_proof_expires() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.set_cache_ttl(Some(1000));
+    proxy.prove_and_cache_at(100).expect_success();
+
+    // the cache answers while the provider refuses the account
+    proxy.set_answer(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        "is_kyc_proved",
+        proxy.admin_account.1,
+        false,
+    );
+    proxy.is_kyc_proved_at(true, 1099).expect_success();
+    proxy.is_kyc_proved_at(false, 1100).expect_success();
+}
+
+#[test]
+fn test_cached_proof_expires_on_ban_lapse() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "second");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc_provider(second_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_three.1,
+        second_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.set_aggregation_policy(POLICY_ALL, 0);
+    proxy
+        .ban_provider_until_at(proxy.admin_account.1, first_provider_package_hash, 1000, 0)
+        .expect_success();
+    proxy.set_cache_ttl(Some(5000));
+    proxy.prove_and_cache_at(100).expect_success();
+
+    // the first provider refuses the account once its ban lapses, well within the TTL
+    proxy.is_kyc_proved_at(true, 999).expect_success();
+    proxy.is_kyc_proved_at(false, 1000).expect_success();
+}
+
+#[test]
+fn test_cache_disabled_by_default() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.prove_and_cache_at(0).expect_success();
+
+    proxy.set_answer(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        "is_kyc_proved",
+        proxy.admin_account.1,
+        false,
+    );
+    proxy.is_kyc_proved_at(false, 1).expect_success();
+}
+
+#[test]
+fn test_cache_flushed_on_ban() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, _second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "second");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc_provider(second_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.set_cache_ttl(Some(1000));
+    proxy.prove_and_cache_at(0).expect_success();
+    proxy.is_kyc_proved_at(true, 1).expect_success();
+
+    proxy.ban_provider(first_provider_package_hash);
+    proxy.is_kyc_proved_at(false, 2).expect_success();
+}
+
+#[test]
+fn test_cache_expired_on_weight_change() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy
+        .set_provider_weight(first_provider_package_hash, 2)
+        .expect_success();
+    proxy.set_aggregation_policy(POLICY_WEIGHTED, 2);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.set_cache_ttl(Some(1000));
+    proxy.prove_and_cache_at(0).expect_success();
+    proxy.is_kyc_proved_at(true, 1).expect_success();
+
+    // the approval alone no longer reaches the threshold
+    proxy
+        .set_provider_weight(first_provider_package_hash, 1)
+        .expect_success();
+    proxy.is_kyc_proved_at(false, 2).expect_success();
+}
+
+#[test]
+fn test_cache_expired_on_unban() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, _second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "second");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc_provider(second_provider_package_hash);
+    proxy.set_aggregation_policy(POLICY_ALL, 0);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.ban_provider(second_provider_package_hash);
+    proxy.set_cache_ttl(Some(1000));
+    proxy.prove_and_cache_at(0).expect_success();
+    proxy.is_kyc_proved_at(true, 1).expect_success();
+
+    // the unbanned provider refuses the account, which fails the policy
+    proxy.unban_provider(second_provider_package_hash);
+    proxy.is_kyc_proved_at(false, 2).expect_success();
+}
+
+#[test]
+fn test_invalidate_cached_proof() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.set_cache_ttl(Some(1000));
+    proxy.prove_and_cache_at(0).expect_success();
+    proxy.set_answer(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        "is_kyc_proved",
+        proxy.admin_account.1,
+        false,
+    );
+    proxy.is_kyc_proved_at(true, 1).expect_success();
+
+    proxy.invalidate_cached_proof();
+    proxy.is_kyc_proved_at(false, 2).expect_success();
+
+    // a refused account is not cached, flushing drops the approvals cached before
+    proxy.prove_and_cache_at(3).expect_success();
+    proxy.is_kyc_proved_at(false, 4).expect_success();
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy.prove_and_cache_at(5).expect_success();
+    proxy.set_answer(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        "is_kyc_proved",
+        proxy.admin_account.1,
+        false,
+    );
+    proxy.flush_cache();
+    proxy.is_kyc_proved_at(false, 6).expect_success();
+}
+
+#[test]
+#[should_panic = "User(301)"]
+fn test_set_cache_ttl_not_admin() {
+    let mut proxy = ProxyContract::deploy();
+    proxy
+        .set_cache_ttl_as(proxy.participant_two.1, Some(1000))
+        .expect_success();
+}
+
+#[test]
+fn test_ban_provider_until() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy
+        .ban_provider_until_at(proxy.admin_account.1, first_provider_package_hash, 1000, 0)
+        .expect_success();
+    let (banned, ban_expiry, _) = proxy.provider_record(first_provider_package_hash).unwrap();
+    assert!(banned);
+    assert_eq!(ban_expiry, Some(1000));
+
+    proxy.is_kyc_proved_at(false, 999).expect_success();
+    // the ban lapses without unbanning the provider
+    proxy.is_kyc_proved_at(true, 1000).expect_success();
+}
+
+#[test]
+fn test_get_ban_expiry() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    let args = runtime_args! {"provider" => Key::Hash(first_provider_package_hash.value())};
+    let ban_expiry: Option<u64> = proxy.query_at("get_ban_expiry", args.clone(), 0);
+    assert_eq!(ban_expiry, None);
+
+    proxy
+        .ban_provider_until_at(proxy.admin_account.1, first_provider_package_hash, 1000, 0)
+        .expect_success();
+    let ban_expiry: Option<u64> = proxy.query_at("get_ban_expiry", args.clone(), 999);
+    assert_eq!(ban_expiry, Some(1000));
+    // the record keeps the expiry, but the lapsed ban is no longer reported
+    let ban_expiry: Option<u64> = proxy.query_at("get_ban_expiry", args.clone(), 1000);
+    assert_eq!(ban_expiry, None);
+
+    proxy.ban_provider(first_provider_package_hash);
+    let ban_expiry: Option<u64> = proxy.query_at("get_ban_expiry", args, 2000);
+    assert_eq!(ban_expiry, None);
+}
+
+#[test]
+fn test_ban_provider_until_unbanned_early() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy
+        .ban_provider_until_at(proxy.admin_account.1, first_provider_package_hash, 1000, 0)
+        .expect_success();
+    proxy.unban_provider(first_provider_package_hash);
+    proxy.is_kyc_proved_at(true, 500).expect_success();
+}
+
+#[test]
+fn test_ban_provider_until_made_permanent() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy.add_kyc(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+    );
+    proxy
+        .ban_provider_until_at(proxy.admin_account.1, first_provider_package_hash, 1000, 0)
+        .expect_success();
+    proxy.ban_provider(first_provider_package_hash);
+    proxy.is_kyc_proved_at(false, 2000).expect_success();
+}
+
+#[test]
+#[should_panic = "User(314)"]
+fn test_ban_provider_until_past() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy
+        .ban_provider_until_at(proxy.admin_account.1, first_provider_package_hash, 100, 200)
+        .expect_success();
+}
+
+#[test]
+#[should_panic = "User(301)"]
+fn test_ban_provider_until_not_operator() {
+    let mut proxy = ProxyContract::deploy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_kyc_provider(first_provider_package_hash);
+    proxy
+        .ban_provider_until_at(
+            proxy.participant_two.1,
+            first_provider_package_hash,
+            1000,
+            0,
+        )
+        .expect_success();
+}
+
+#[test]
+fn test_synth_proxy_without_providers() {
+    let mut proxy = ProxyContract::deploy_synth_proxy();
+    proxy.is_enabled(false).expect_success();
+    proxy.is_allowed(U512::from(100), false).expect_success();
+}
+
+#[test]
+fn test_synth_proxy_has_no_cache() {
+    let proxy = ProxyContract::deploy_synth_proxy();
+    let contract = proxy
+        .builder
+        .get_contract(ContractHash::new(proxy.contract_hash))
+        .expect("should have contract.");
+    for name in ["synth_attestations", "cache_ttl", "cache_epoch"] {
+        assert!(!contract.named_keys().contains_key(name));
+    }
+}
+
+#[test]
+fn test_synth_proxy_enabled() {
+    let mut proxy = ProxyContract::deploy_synth_proxy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_synth_provider(first_provider_package_hash);
+    proxy.is_enabled(false).expect_success();
+
+    proxy.set_answer(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        "is_enabled",
+        proxy.admin_account.1,
+        true,
+    );
+    proxy.is_enabled(true).expect_success();
+    // the checks are answered independently
+    proxy.is_allowed(U512::from(100), false).expect_success();
+}
+
+#[test]
+fn test_synth_proxy_allowed_amount() {
+    let mut proxy = ProxyContract::deploy_synth_proxy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy.add_synth_provider(first_provider_package_hash);
+    proxy.set_answer(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        "is_allowed",
+        proxy.admin_account.1,
+        true,
+    );
+    proxy.is_allowed(U512::zero(), true).expect_success();
+    proxy
+        .is_allowed(U512::from(1_000_000_000_000u64), true)
+        .expect_success();
+
+    proxy.set_max_amount(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+        Some(U512::from(100)),
+    );
+    proxy.is_allowed(U512::from(99), true).expect_success();
+    proxy.is_allowed(U512::from(100), true).expect_success();
+    proxy.is_allowed(U512::from(101), false).expect_success();
+}
+
+#[test]
+fn test_synth_proxy_multiple_providers() {
+    let mut proxy = ProxyContract::deploy_synth_proxy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "second");
+    proxy.add_synth_provider(first_provider_package_hash);
+    proxy.add_synth_provider(second_provider_package_hash);
+    proxy.is_allowed(U512::from(500), false).expect_success();
+
+    // the first provider caps the amount, the second one approves it
+    proxy.set_answer(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        "is_allowed",
+        proxy.admin_account.1,
+        true,
+    );
+    proxy.set_max_amount(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        proxy.admin_account.1,
+        Some(U512::from(100)),
+    );
+    proxy.is_allowed(U512::from(500), false).expect_success();
+    proxy.set_answer(
+        proxy.participant_three.1,
+        second_provider_hash.value(),
+        "is_allowed",
+        proxy.admin_account.1,
+        true,
+    );
+    proxy.is_allowed(U512::from(500), true).expect_success();
+
+    proxy.set_answer(
+        proxy.participant_three.1,
+        second_provider_hash.value(),
+        "is_enabled",
+        proxy.admin_account.1,
+        true,
+    );
+    proxy.is_enabled(true).expect_success();
+}
+
+#[test]
+fn test_synth_proxy_banned_provider() {
+    let mut proxy = ProxyContract::deploy_synth_proxy();
+    let (first_provider_package_hash, first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    let (second_provider_package_hash, _second_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_three.1, "second");
+    proxy.add_synth_provider(first_provider_package_hash);
+    proxy.add_synth_provider(second_provider_package_hash);
+    proxy.set_answer(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        "is_enabled",
+        proxy.admin_account.1,
+        true,
+    );
+    proxy.set_answer(
+        proxy.participant_two.1,
+        first_provider_hash.value(),
+        "is_allowed",
+        proxy.admin_account.1,
+        true,
+    );
+    proxy.is_enabled(true).expect_success();
+
+    proxy.ban_synth_provider(first_provider_package_hash);
+    proxy.is_enabled(false).expect_success();
+    proxy.is_allowed(U512::from(100), false).expect_success();
+
+    proxy.unban_synth_provider(first_provider_package_hash);
+    proxy.is_enabled(true).expect_success();
+    proxy.is_allowed(U512::from(100), true).expect_success();
+}
+
+#[test]
+#[should_panic = "User(301)"]
+fn test_synth_proxy_add_provider_not_manager() {
+    let mut proxy = ProxyContract::deploy_synth_proxy();
+    let (first_provider_package_hash, _first_provider_hash) =
+        proxy.deploy_kyc(proxy.participant_two.1, "first");
+    proxy
+        .add_synth_provider_as(proxy.participant_two.1, first_provider_package_hash)
+        .expect_success();
+}